@@ -0,0 +1,123 @@
+use std::{convert::TryFrom, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::history::color::ColorInfo;
+
+/// Filters images by brightness and colors. Checked after download, since colors are only known
+/// from the image content. Images that cannot be decoded are rejected.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColorFilter {
+	pub enable: bool,
+	/// Bounds of average luminance, from 0.0 (black) to 1.0 (white)
+	pub min_luminance: f32,
+	pub max_luminance: f32,
+	/// Image palette must contain a color near any of these. Empty means any color.
+	pub colors: Vec<Color>,
+	/// Maximum distance between colors (0-441) to be considered near
+	pub distance: f32,
+}
+
+impl Default for ColorFilter {
+	fn default() -> Self {
+		ColorFilter {
+			enable: false,
+			min_luminance: 0.0,
+			max_luminance: 1.0,
+			colors: Vec::new(),
+			distance: 60.0,
+		}
+	}
+}
+
+impl ColorFilter {
+	/// Returns the reason the image is rejected, if any. `info` is `None` when the image cannot
+	/// be decoded.
+	pub fn check(&self, info: Option<&ColorInfo>) -> Result<(), String> {
+		if !self.enable {
+			return Ok(());
+		}
+		let info = match info {
+			Some(info) => info,
+			None => return Err("image cannot be decoded to check colors".to_string()),
+		};
+		if info.luminance < self.min_luminance {
+			return Err(format!(
+				"luminance {:.2} is below {:.2}",
+				info.luminance, self.min_luminance
+			));
+		}
+		if info.luminance > self.max_luminance {
+			return Err(format!(
+				"luminance {:.2} is above {:.2}",
+				info.luminance, self.max_luminance
+			));
+		}
+		let near = |color: &Color| {
+			info.palette
+				.iter()
+				.any(|c| c.distance(color) <= self.distance)
+		};
+		if !self.colors.is_empty() && !self.colors.iter().any(near) {
+			let colors: Vec<String> = self.colors.iter().map(Color::to_string).collect();
+			return Err(format!("no color near {}", colors.join(", ")));
+		}
+		Ok(())
+	}
+}
+
+/// RGB color, formatted as `#RRGGBB`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+	pub r: u8,
+	pub g: u8,
+	pub b: u8,
+}
+
+impl Color {
+	/// Euclidean distance in RGB space.
+	pub fn distance(&self, other: &Color) -> f32 {
+		let d = |a: u8, b: u8| (a as f32 - b as f32).powi(2);
+		(d(self.r, other.r) + d(self.g, other.g) + d(self.b, other.b)).sqrt()
+	}
+}
+
+impl Display for Color {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+	}
+}
+
+impl FromStr for Color {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let err = || format!("'{}' is not a valid color. example: #1e90ff", s);
+		let hex = s.trim().trim_start_matches('#');
+		if hex.len() != 6 || !hex.is_ascii() {
+			return Err(err());
+		}
+		let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| err());
+		Ok(Color {
+			r: channel(0)?,
+			g: channel(2)?,
+			b: channel(4)?,
+		})
+	}
+}
+
+impl TryFrom<String> for Color {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<Color> for String {
+	fn from(color: Color) -> Self {
+		color.to_string()
+	}
+}
@@ -11,14 +11,24 @@ use serde::{Deserialize, Serialize};
 use tokio::fs;
 
 use super::{
-	configuration::{AspectRatio, Configuration, MinimumSize, Subreddit},
+	configuration::{
+		AspectRatio, Configuration, DuplicateStrategy, MinimumSize, StorageStrategy, Subreddit,
+	},
+	imgur::ImgurConfig,
+	metadata::MetadataConfig,
+	reddit::RedditConfig,
 	server::ServerConfig,
+	source::SourceConfig,
 };
 
 pub static CONFIG_FILENAME: &str = "ridit.toml";
 
 pub type Subreddits = BTreeMap<String, Subreddit>;
 pub type Settings = BTreeMap<String, Configuration>;
+pub type Sources = BTreeMap<String, SourceConfig>;
+
+/// Keeps the original layout of `{subreddit}/{original file name}`.
+pub static DEFAULT_FILENAME_TEMPLATE: &str = "{subreddit}/{filename}";
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
@@ -28,9 +38,21 @@ pub struct Config {
 	pub timeout: u32,
 	pub download_threads: usize,
 	pub path: PathBuf,
+	/// How to handle images with the same content as already stored images
+	pub duplicate: DuplicateStrategy,
+	/// How to store an image that matches more than one profile
+	pub storage: StorageStrategy,
+	/// Template of file location inside profile directory. See `DownloadMeta::render_filename`
+	/// for supported placeholders.
+	pub filename_template: String,
 	pub server: ServerConfig,
+	pub imgur: ImgurConfig,
+	pub metadata: MetadataConfig,
+	pub reddit: RedditConfig,
 	pub settings: Settings,
 	pub subreddits: Subreddits,
+	/// Image sources other than subreddits
+	pub sources: Sources,
 }
 
 impl Config {
@@ -41,6 +63,22 @@ impl Config {
 			.with_context(|| format!("profile {} does not exist!", active))?)
 	}
 
+	/// Gets file naming template for the profile, falling back to the global template.
+	pub fn filename_template(&self, profile: &str) -> &str {
+		match self.settings.get(profile) {
+			Some(setting) if !setting.filename_template.is_empty() => &setting.filename_template,
+			_ if !self.filename_template.is_empty() => &self.filename_template,
+			_ => DEFAULT_FILENAME_TEMPLATE,
+		}
+	}
+
+	/// Checks if any of the profiles accepts the image format.
+	pub fn accepts_format(&self, format: &str) -> bool {
+		self.settings
+			.values()
+			.any(|setting| setting.accepts_format(format))
+	}
+
 	pub fn get_configuration(&self) -> Result<&Configuration> {
 		Ok(self
 			.get(&self.focused_profile)
@@ -87,6 +125,7 @@ impl Default for Config {
 				height: 1920,
 				width: 1080,
 			},
+			..Configuration::default()
 		};
 		m.insert("mobile".to_string(), mobile_config);
 		let p = match UserDirs::new()
@@ -104,14 +143,21 @@ impl Default for Config {
 			path: p,
 			download_threads: 8,
 			timeout: 10,
+			duplicate: DuplicateStrategy::default(),
+			storage: StorageStrategy::default(),
+			filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
 			settings: m,
 			subreddits: subs,
+			sources: Sources::new(),
 			server: ServerConfig::default(),
+			imgur: ImgurConfig::default(),
+			metadata: MetadataConfig::default(),
+			reddit: RedditConfig::default(),
 		}
 	}
 }
 
-fn project_dir() -> ProjectDirs {
+pub fn project_dir() -> ProjectDirs {
 	ProjectDirs::from("id.web", "tigor", "ridit")
 		.context("failed to get project directory")
 		.unwrap()
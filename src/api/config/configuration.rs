@@ -1,12 +1,80 @@
-use std::{convert::Infallible, default::Default, fmt::Display, str::FromStr};
+use std::{
+	convert::{Infallible, TryFrom},
+	default::Default,
+	fmt::Display,
+	str::FromStr,
+};
 
 use pad::PadStr;
 use serde::{Deserialize, Serialize};
 
+use super::{color::ColorFilter, filter::PostFilter, policy::PostPolicy, transform::Transform};
+
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct Configuration {
+	/// Accepted image formats, e.g. `jpg`, `png`, `webp`.
+	#[serde(default = "default_formats")]
+	pub formats: Vec<String>,
+	/// Overrides file naming template for this profile. Empty means using the global template.
+	#[serde(default)]
+	pub filename_template: String,
+	#[serde(default)]
+	pub orientation: Orientation,
+	#[serde(default)]
+	pub download_size: DownloadSize,
 	pub aspect_ratio: AspectRatio,
 	pub minimum_size: MinimumSize,
+	#[serde(default)]
+	pub maximum_size: MaximumSize,
+	#[serde(default)]
+	pub exact_resolution: ExactResolution,
+	#[serde(default)]
+	pub file_size: FileSize,
+	#[serde(default)]
+	pub filter: PostFilter,
+	#[serde(default)]
+	pub near_duplicate: NearDuplicate,
+	#[serde(default)]
+	pub color: ColorFilter,
+	#[serde(default)]
+	pub transform: Transform,
+}
+
+fn default_formats() -> Vec<String> {
+	vec!["jpg".to_string(), "png".to_string()]
+}
+
+impl Configuration {
+	/// Aspect ratio to crop to when transforming images. `None` when the aspect ratio filter is
+	/// disabled or the ratio is not set.
+	pub fn crop_ratio(&self) -> Option<(u32, u32)> {
+		if !self.aspect_ratio.enable {
+			return None;
+		}
+		match (self.aspect_ratio.width, self.aspect_ratio.height) {
+			(0, _) | (_, 0) => None,
+			ratio => Some(ratio),
+		}
+	}
+
+	/// Checks if the image format (file extension) is accepted by this profile.
+	/// `jpg` and `jpeg` are treated as the same format.
+	pub fn accepts_format(&self, format: &str) -> bool {
+		let format = normalize_format(format);
+		self.formats
+			.iter()
+			.any(|accepted| normalize_format(accepted) == format)
+	}
+}
+
+/// Lowercases the format and maps aliases (e.g. `jpeg`) to their common name.
+pub fn normalize_format(format: &str) -> String {
+	let format = format.to_lowercase();
+	match format.as_str() {
+		"jpeg" | "jpe" | "jfif" => "jpg".to_string(),
+		"tif" => "tiff".to_string(),
+		_ => format,
+	}
 }
 
 impl Default for Configuration {
@@ -14,6 +82,17 @@ impl Default for Configuration {
 		Configuration {
 			aspect_ratio: AspectRatio::default(),
 			minimum_size: MinimumSize::default(),
+			maximum_size: MaximumSize::default(),
+			exact_resolution: ExactResolution::default(),
+			file_size: FileSize::default(),
+			filter: PostFilter::default(),
+			orientation: Orientation::default(),
+			download_size: DownloadSize::default(),
+			near_duplicate: NearDuplicate::default(),
+			color: ColorFilter::default(),
+			transform: Transform::default(),
+			formats: default_formats(),
+			filename_template: String::new(),
 		}
 	}
 }
@@ -54,12 +133,249 @@ impl Default for MinimumSize {
 	}
 }
 
+/// Rejects images larger than the given size, e.g. very wide panoramas.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+pub struct MaximumSize {
+	pub enable: bool,
+	pub height: u32,
+	pub width: u32,
+}
+
+impl Default for MaximumSize {
+	fn default() -> Self {
+		MaximumSize {
+			enable: false,
+			height: 4320,
+			width: 7680,
+		}
+	}
+}
+
+/// Only accepts images with one of the given resolutions.
+#[derive(Debug, Deserialize, Clone, Serialize, Default)]
+pub struct ExactResolution {
+	pub enable: bool,
+	/// Maximum difference (in pixels) of width and height from the resolution.
+	pub tolerance: u32,
+	pub resolutions: Vec<Resolution>,
+}
+
+impl ExactResolution {
+	pub fn matches(&self, width: u32, height: u32) -> bool {
+		self.resolutions.iter().any(|res| {
+			res.width.abs_diff(width) <= self.tolerance
+				&& res.height.abs_diff(height) <= self.tolerance
+		})
+	}
+}
+
+/// Image resolution, formatted as `{width}x{height}`, e.g. `1920x1080`.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Resolution {
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Display for Resolution {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}x{}", self.width, self.height)
+	}
+}
+
+impl FromStr for Resolution {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let err = || format!("'{}' is not a valid resolution. example: 1920x1080", s);
+		let (width, height) = s.trim().split_once(['x', 'X']).ok_or_else(err)?;
+		Ok(Resolution {
+			width: width.trim().parse().map_err(|_| err())?,
+			height: height.trim().parse().map_err(|_| err())?,
+		})
+	}
+}
+
+impl TryFrom<String> for Resolution {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<Resolution> for String {
+	fn from(res: Resolution) -> Self {
+		res.to_string()
+	}
+}
+
+/// Bounds of image file size in bytes. Checked against `Content-Length` before download and the
+/// actual file size after.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, Default)]
+pub struct FileSize {
+	pub enable: bool,
+	pub minimum: u64,
+	/// 0 means no limit
+	pub maximum: u64,
+}
+
+impl FileSize {
+	pub fn accepts(&self, size: u64) -> bool {
+		if !self.enable {
+			return true;
+		}
+		size >= self.minimum && (self.maximum == 0 || size <= self.maximum)
+	}
+}
+
+/// Accepted image orientation. Images with width and height within 2% of each other are
+/// considered square.
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+	#[default]
+	Any,
+	Landscape,
+	Portrait,
+	Square,
+}
+
+impl Orientation {
+	const SQUARE_TOLERANCE: f32 = 0.02;
+
+	pub fn of(width: u32, height: u32) -> Self {
+		let ratio = width as f32 / height as f32;
+		if (ratio - 1.0).abs() <= Self::SQUARE_TOLERANCE {
+			Self::Square
+		} else if ratio > 1.0 {
+			Self::Landscape
+		} else {
+			Self::Portrait
+		}
+	}
+
+	pub fn accepts(&self, width: u32, height: u32) -> bool {
+		*self == Self::Any || *self == Self::of(width, height)
+	}
+}
+
+impl Display for Orientation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Any => write!(f, "any"),
+			Self::Landscape => write!(f, "landscape"),
+			Self::Portrait => write!(f, "portrait"),
+			Self::Square => write!(f, "square"),
+		}
+	}
+}
+
+impl FromStr for Orientation {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"landscape" | "l" => Self::Landscape,
+			"portrait" | "p" => Self::Portrait,
+			"square" | "s" => Self::Square,
+			_ => Self::Any,
+		})
+	}
+}
+
+/// Which size of the image is downloaded. Reddit serves smaller versions of posted images, so
+/// profiles for small screens do not have to download the full size source.
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum DownloadSize {
+	/// Full size image as posted
+	#[default]
+	Source,
+	/// Smallest available version with at least the given width and height. Falls back to source
+	/// when no smaller version is large enough.
+	AtLeast(Resolution),
+}
+
+impl Display for DownloadSize {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Source => write!(f, "source"),
+			Self::AtLeast(res) => write!(f, "{}", res),
+		}
+	}
+}
+
+impl FromStr for DownloadSize {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.trim().to_lowercase().as_str() {
+			"source" | "original" => Ok(Self::Source),
+			_ => s.parse().map(Self::AtLeast),
+		}
+	}
+}
+
+impl TryFrom<String> for DownloadSize {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<DownloadSize> for String {
+	fn from(size: DownloadSize) -> Self {
+		size.to_string()
+	}
+}
+
+/// Rejects images that look like an already stored image in the same profile, e.g. re-encoded or
+/// resized reposts. The higher resolution copy is kept.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+pub struct NearDuplicate {
+	pub enable: bool,
+	/// Maximum number of differing bits (out of 64) between perceptual hashes to be considered
+	/// the same image.
+	pub distance: u32,
+}
+
+impl Default for NearDuplicate {
+	fn default() -> Self {
+		NearDuplicate {
+			enable: false,
+			distance: 5,
+		}
+	}
+}
+
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct Subreddit {
 	pub proper_name: String,
 	pub nsfw: bool,
 	pub download_first: bool,
 	pub sort: Sort,
+	/// Time window for `top` and `controversial` sorts.
+	#[serde(default)]
+	pub time: TimeRange,
+	/// Maximum number of listing pages (100 posts each) to fetch per run.
+	#[serde(default = "default_max_pages")]
+	pub max_pages: u32,
+	/// Profiles this subreddit feeds images to. Empty means every profile.
+	#[serde(default)]
+	pub profiles: Vec<String>,
+	/// Profiles this subreddit never feeds images to.
+	#[serde(default)]
+	pub exclude_profiles: Vec<String>,
+	#[serde(default)]
+	pub filter: PostFilter,
+	#[serde(default)]
+	pub policy: PostPolicy,
+}
+
+fn default_max_pages() -> u32 {
+	1
 }
 
 impl Subreddit {
@@ -69,9 +385,23 @@ impl Subreddit {
 			nsfw: true,
 			download_first: false,
 			sort: Sort::New,
+			time: TimeRange::default(),
+			max_pages: default_max_pages(),
+			profiles: Vec::new(),
+			exclude_profiles: Vec::new(),
+			filter: PostFilter::default(),
+			policy: PostPolicy::default(),
 		}
 	}
 
+	/// Checks if images from this subreddit may be stored in the profile.
+	pub fn accepts_profile(&self, profile: &str) -> bool {
+		if self.exclude_profiles.iter().any(|p| p == profile) {
+			return false;
+		}
+		self.profiles.is_empty() || self.profiles.iter().any(|p| p == profile)
+	}
+
 	pub fn padded_proper_name(&self) -> String {
 		("[".to_string() + &self.proper_name + "]").pad_to_width(23)
 	}
@@ -118,3 +448,128 @@ impl FromStr for Sort {
 		})
 	}
 }
+
+impl Sort {
+	/// Returns true if the sort accepts a time window (`t` query parameter).
+	pub fn is_timed(&self) -> bool {
+		matches!(self, Self::Top | Self::Controversial)
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+	Hour,
+	#[default]
+	Day,
+	Week,
+	Month,
+	Year,
+	All,
+}
+
+impl Display for TimeRange {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Hour => write!(f, "hour"),
+			Self::Day => write!(f, "day"),
+			Self::Week => write!(f, "week"),
+			Self::Month => write!(f, "month"),
+			Self::Year => write!(f, "year"),
+			Self::All => write!(f, "all"),
+		}
+	}
+}
+
+impl FromStr for TimeRange {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"hour" => Self::Hour,
+			"week" => Self::Week,
+			"month" => Self::Month,
+			"year" => Self::Year,
+			"all" => Self::All,
+			_ => Self::Day,
+		})
+	}
+}
+
+/// What to do when a downloaded image has the same content as an already stored image in the
+/// same profile.
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateStrategy {
+	/// Do not store the duplicate
+	#[default]
+	Skip,
+	/// Store the duplicate as a hard link to the existing file. Falls back to copy when linking fails.
+	Hardlink,
+	/// Store the duplicate as usual
+	Keep,
+}
+
+impl Display for DuplicateStrategy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Skip => write!(f, "skip"),
+			Self::Hardlink => write!(f, "hardlink"),
+			Self::Keep => write!(f, "keep"),
+		}
+	}
+}
+
+impl FromStr for DuplicateStrategy {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"hardlink" | "link" => Self::Hardlink,
+			"keep" => Self::Keep,
+			_ => Self::Skip,
+		})
+	}
+}
+
+/// How an image is stored when it matches more than one profile. The first profile always gets a
+/// regular copy, the other profiles refer to that copy. Falls back to copy when the filesystem
+/// does not support the strategy, e.g. profiles on different filesystems.
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageStrategy {
+	/// Store a full copy on every profile
+	#[default]
+	Copy,
+	/// Hard link to the first copy
+	Hardlink,
+	/// Symbolic link to the first copy. The link breaks when the first copy is removed, prefer
+	/// hard link when possible
+	Symlink,
+	/// Copy on write clone of the first copy. Only supported by some filesystems like btrfs, xfs and apfs
+	Reflink,
+}
+
+impl Display for StorageStrategy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Copy => write!(f, "copy"),
+			Self::Hardlink => write!(f, "hardlink"),
+			Self::Symlink => write!(f, "symlink"),
+			Self::Reflink => write!(f, "reflink"),
+		}
+	}
+}
+
+impl FromStr for StorageStrategy {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"hardlink" | "link" => Self::Hardlink,
+			"symlink" => Self::Symlink,
+			"reflink" | "clone" => Self::Reflink,
+			_ => Self::Copy,
+		})
+	}
+}
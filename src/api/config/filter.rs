@@ -0,0 +1,153 @@
+use std::{convert::TryFrom, fmt::Display};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::api::reddit::models::download_meta::DownloadMeta;
+
+/// Includes or excludes posts by title, flair, author and post statistics.
+///
+/// Keywords are matched case insensitively against the post title. A keyword wrapped in slashes,
+/// e.g. `/^\[OC\]/`, is treated as regular expression. Flairs and authors must match exactly,
+/// ignoring case. Empty lists and zero values disable the rule.
+#[derive(Debug, Deserialize, Clone, Serialize, Default)]
+#[serde(default)]
+pub struct PostFilter {
+	/// Title must match at least one of these
+	pub keywords: Vec<Keyword>,
+	/// Title must not match any of these
+	pub exclude_keywords: Vec<Keyword>,
+	/// Link flair must be one of these
+	pub flairs: Vec<String>,
+	pub exclude_flairs: Vec<String>,
+	/// Author must be one of these
+	pub authors: Vec<String>,
+	pub exclude_authors: Vec<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub min_score: Option<i64>,
+	pub min_upvote_ratio: f32,
+	pub min_comments: u32,
+}
+
+impl PostFilter {
+	/// Checks the post against the rules. Returns the reason on rejection.
+	///
+	/// Rules on post statistics pass when the information is not available, e.g. images from
+	/// local folders.
+	pub fn check(&self, meta: &DownloadMeta) -> Result<(), String> {
+		let title = meta.title.to_lowercase();
+		if !self.keywords.is_empty() && !self.keywords.iter().any(|k| k.matches(&title)) {
+			return Err("title does not match any keyword".to_string());
+		}
+		if let Some(k) = self.exclude_keywords.iter().find(|k| k.matches(&title)) {
+			return Err(format!("title matches excluded keyword '{}'", k));
+		}
+
+		let flair = meta.flair.as_deref().unwrap_or_default();
+		if !self.flairs.is_empty() && !contains(&self.flairs, flair) {
+			return Err(format!("flair '{}' is not included", flair));
+		}
+		if contains(&self.exclude_flairs, flair) {
+			return Err(format!("flair '{}' is excluded", flair));
+		}
+
+		// images without author, e.g. from local folders, are not checked.
+		let has_author = !meta.author.is_empty();
+		if has_author && !self.authors.is_empty() && !contains(&self.authors, &meta.author) {
+			return Err(format!("author '{}' is not included", meta.author));
+		}
+		if contains(&self.exclude_authors, &meta.author) {
+			return Err(format!("author '{}' is excluded", meta.author));
+		}
+
+		if let (Some(min), Some(score)) = (self.min_score, meta.score) {
+			if score < min {
+				return Err(format!("score {} is below {}", score, min));
+			}
+		}
+		if let Some(ratio) = meta.upvote_ratio {
+			if ratio < self.min_upvote_ratio {
+				return Err(format!(
+					"upvote ratio {} is below {}",
+					ratio, self.min_upvote_ratio
+				));
+			}
+		}
+		if let Some(comments) = meta.num_comments {
+			if comments < self.min_comments {
+				return Err(format!(
+					"{} comments is below {}",
+					comments, self.min_comments
+				));
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Regular expression of the keyword if the keyword is wrapped in slashes.
+fn keyword_regex(keyword: &str) -> Option<&str> {
+	keyword
+		.strip_prefix('/')
+		.and_then(|k| k.strip_suffix('/'))
+		.filter(|k| !k.is_empty())
+}
+
+/// Title keyword, compiled once when the configuration is loaded. Invalid regular expressions
+/// fail the configuration loading.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Keyword {
+	keyword: String,
+	matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+	/// Lowercased keyword
+	Text(String),
+	Regex(Regex),
+}
+
+impl Keyword {
+	/// Checks the keyword against lowercased text.
+	fn matches(&self, text: &str) -> bool {
+		match &self.matcher {
+			Matcher::Text(keyword) => text.contains(keyword.as_str()),
+			Matcher::Regex(re) => re.is_match(text),
+		}
+	}
+}
+
+impl TryFrom<String> for Keyword {
+	type Error = String;
+
+	fn try_from(keyword: String) -> Result<Self, Self::Error> {
+		let matcher = match keyword_regex(&keyword) {
+			Some(pattern) => Matcher::Regex(
+				RegexBuilder::new(pattern)
+					.case_insensitive(true)
+					.build()
+					.map_err(|err| format!("invalid keyword regex '{}': {}", keyword, err))?,
+			),
+			None => Matcher::Text(keyword.to_lowercase()),
+		};
+		Ok(Keyword { keyword, matcher })
+	}
+}
+
+impl From<Keyword> for String {
+	fn from(keyword: Keyword) -> Self {
+		keyword.keyword
+	}
+}
+
+impl Display for Keyword {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.keyword)
+	}
+}
+
+fn contains(list: &[String], value: &str) -> bool {
+	!value.is_empty() && list.iter().any(|v| v.eq_ignore_ascii_case(value))
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ImgurConfig {
+	/// Imgur API client id. Albums are not resolved when empty.
+	pub client_id: String,
+	pub api_base: String,
+}
+
+impl Default for ImgurConfig {
+	fn default() -> Self {
+		ImgurConfig {
+			client_id: String::new(),
+			api_base: "https://api.imgur.com".to_string(),
+		}
+	}
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Where to store post information (title, author, permalink, etc.) of downloaded images.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MetadataConfig {
+	/// Embeds XMP metadata into jpeg and png files
+	pub embed: bool,
+	/// Writes `{image}.json` file next to each downloaded image
+	pub sidecar: bool,
+}
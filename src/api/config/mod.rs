@@ -1,3 +1,11 @@
+pub mod color;
 pub mod config;
 pub mod configuration;
+pub mod filter;
+pub mod imgur;
+pub mod metadata;
+pub mod policy;
+pub mod reddit;
 pub mod server;
+pub mod source;
+pub mod transform;
@@ -0,0 +1,108 @@
+use std::{convert::Infallible, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::reddit::models::listing::ChildrenData;
+
+/// How to treat special kinds of posts in a subreddit.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+#[serde(default)]
+pub struct PostPolicy {
+	/// Posts pinned to the top of the subreddit, usually announcements
+	pub stickied: Policy,
+	/// Posts removed by moderators, reddit or the author
+	pub removed: Policy,
+	/// Posts whose author account is deleted
+	pub deleted: Policy,
+	/// Posts tagged as spoiler
+	pub spoiler: Policy,
+	pub crosspost: CrosspostPolicy,
+}
+
+impl Default for PostPolicy {
+	fn default() -> Self {
+		PostPolicy {
+			stickied: Policy::Skip,
+			removed: Policy::Skip,
+			deleted: Policy::Skip,
+			spoiler: Policy::Allow,
+			crosspost: CrosspostPolicy::Resolve,
+		}
+	}
+}
+
+impl PostPolicy {
+	/// Checks the post against stickied, removed, deleted and spoiler policies.
+	pub fn accepts(&self, post: &ChildrenData) -> bool {
+		!(self.stickied.skips(post.stickied)
+			|| self.removed.skips(post.removed_by_category.is_some())
+			|| self.deleted.skips(post.author == "[deleted]")
+			|| self.spoiler.skips(post.spoiler.unwrap_or(false)))
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+	Allow,
+	Skip,
+}
+
+impl Policy {
+	fn skips(&self, matched: bool) -> bool {
+		matched && *self == Policy::Skip
+	}
+}
+
+impl Display for Policy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Allow => write!(f, "allow"),
+			Self::Skip => write!(f, "skip"),
+		}
+	}
+}
+
+impl FromStr for Policy {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"allow" | "keep" => Self::Allow,
+			_ => Self::Skip,
+		})
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrosspostPolicy {
+	/// Uses the original post instead, so the same image is not downloaded again when it's
+	/// crossposted to other subreddits
+	Resolve,
+	/// Treats crosspost as a normal post
+	Allow,
+	Skip,
+}
+
+impl Display for CrosspostPolicy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Resolve => write!(f, "resolve"),
+			Self::Allow => write!(f, "allow"),
+			Self::Skip => write!(f, "skip"),
+		}
+	}
+}
+
+impl FromStr for CrosspostPolicy {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"allow" | "keep" => Self::Allow,
+			"skip" => Self::Skip,
+			_ => Self::Resolve,
+		})
+	}
+}
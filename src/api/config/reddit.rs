@@ -0,0 +1,89 @@
+use std::{convert::Infallible, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Reddit API access. Anonymous access is used unless an OAuth2 flow is set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedditConfig {
+	pub flow: OAuthFlow,
+	pub client_id: String,
+	/// Empty for installed apps
+	pub client_secret: String,
+	/// Reddit account username. Only used by script flow.
+	pub username: String,
+	/// Reddit account password. Only used by script flow.
+	pub password: String,
+	/// Device id sent on installed flow
+	pub device_id: String,
+	pub token_url: String,
+	/// Base url for authenticated API calls
+	pub api_base: String,
+}
+
+impl Default for RedditConfig {
+	fn default() -> Self {
+		RedditConfig {
+			flow: OAuthFlow::default(),
+			client_id: String::new(),
+			client_secret: String::new(),
+			username: String::new(),
+			password: String::new(),
+			device_id: "DO_NOT_TRACK_THIS_DEVICE".to_string(),
+			token_url: "https://www.reddit.com/api/v1/access_token".to_string(),
+			api_base: "https://oauth.reddit.com".to_string(),
+		}
+	}
+}
+
+impl RedditConfig {
+	/// Copy with the password and client secret masked, for display.
+	pub fn masked(&self) -> Self {
+		let mask = |secret: &str| {
+			if secret.is_empty() {
+				String::new()
+			} else {
+				"********".to_string()
+			}
+		};
+		RedditConfig {
+			client_secret: mask(&self.client_secret),
+			password: mask(&self.password),
+			..self.clone()
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OAuthFlow {
+	/// No authentication
+	#[default]
+	Anonymous,
+	/// Script app, authenticates as the reddit account that owns the app
+	Script,
+	/// Installed app, application only access without a reddit account
+	Installed,
+}
+
+impl Display for OAuthFlow {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Anonymous => write!(f, "anonymous"),
+			Self::Script => write!(f, "script"),
+			Self::Installed => write!(f, "installed"),
+		}
+	}
+}
+
+impl FromStr for OAuthFlow {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"script" => Self::Script,
+			"installed" => Self::Installed,
+			_ => Self::Anonymous,
+		})
+	}
+}
@@ -5,6 +5,13 @@ use std::net::{IpAddr, Ipv4Addr};
 pub struct ServerConfig {
 	pub port: u16,
 	pub ip: IpAddr,
+	/// Interval in minutes between scheduled downloads. 0 disables scheduled download.
+	#[serde(default = "default_download_interval")]
+	pub download_interval: u32,
+}
+
+fn default_download_interval() -> u32 {
+	60
 }
 
 impl Default for ServerConfig {
@@ -12,6 +19,7 @@ impl Default for ServerConfig {
 		ServerConfig {
 			port: 9876,
 			ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+			download_interval: default_download_interval(),
 		}
 	}
 }
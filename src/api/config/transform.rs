@@ -0,0 +1,113 @@
+use std::{convert::Infallible, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Post processing of downloaded images before they are stored in the profile. Disabled by
+/// default, images are stored as downloaded. Only jpeg and png can be transformed, other formats
+/// are stored as downloaded too.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Transform {
+	pub enable: bool,
+	/// How the image is cropped to the profile aspect ratio
+	pub crop: Crop,
+	/// Images larger than this are downscaled to fit. 0 means no limit.
+	pub width: u32,
+	pub height: u32,
+	pub format: OutputFormat,
+	/// Jpeg quality (1-100)
+	pub quality: u8,
+	/// Stores the untouched image under `.originals` directory of the profile
+	pub keep_original: bool,
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Transform {
+			enable: false,
+			crop: Crop::default(),
+			width: 0,
+			height: 0,
+			format: OutputFormat::default(),
+			quality: 90,
+			keep_original: false,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Crop {
+	/// Does not crop the image
+	Off,
+	/// Keeps the center of the image
+	#[default]
+	Center,
+	/// Keeps the most detailed part of the image, trimming the side with less detail first
+	Smart,
+}
+
+impl Display for Crop {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Off => write!(f, "off"),
+			Self::Center => write!(f, "center"),
+			Self::Smart => write!(f, "smart"),
+		}
+	}
+}
+
+impl FromStr for Crop {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"off" | "none" => Self::Off,
+			"smart" | "entropy" => Self::Smart,
+			_ => Self::Center,
+		})
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+	/// Same format as the downloaded image
+	#[default]
+	Original,
+	Jpg,
+	Png,
+}
+
+impl OutputFormat {
+	/// File extension of the output, given the format of the downloaded image.
+	pub fn extension<'a>(&self, source: &'a str) -> &'a str {
+		match self {
+			Self::Original => source,
+			Self::Jpg => "jpg",
+			Self::Png => "png",
+		}
+	}
+}
+
+impl Display for OutputFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Original => write!(f, "original"),
+			Self::Jpg => write!(f, "jpg"),
+			Self::Png => write!(f, "png"),
+		}
+	}
+}
+
+impl FromStr for OutputFormat {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_lowercase().as_str() {
+			"jpg" | "jpeg" => Self::Jpg,
+			"png" => Self::Png,
+			_ => Self::Original,
+		})
+	}
+}
@@ -0,0 +1,61 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use serde::Serialize;
+
+use crate::api::config::color::Color;
+
+/// Size of the downscaled copy colors are computed from.
+const ANALYSIS_SIZE: u32 = 64;
+/// Number of dominant colors kept in the palette.
+const PALETTE_SIZE: usize = 5;
+
+/// Dominant colors and average brightness of an image.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorInfo {
+	/// Average luminance, from 0.0 (black) to 1.0 (white)
+	pub luminance: f32,
+	/// Dominant colors, most common first
+	pub palette: Vec<Color>,
+}
+
+/// Computes dominant colors and average luminance of an image file.
+pub fn analyze_file<P: AsRef<Path>>(path: P) -> Result<ColorInfo> {
+	let path = path.as_ref();
+	let img =
+		image::open(path).with_context(|| format!("failed to decode image {}", path.display()))?;
+	let small = img
+		.resize_exact(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+		.to_rgb8();
+
+	let mut luminance = 0.0;
+	// pixels are grouped by the 4 most significant bits of each channel.
+	let mut buckets: HashMap<(u8, u8, u8), (u32, [u32; 3])> = HashMap::new();
+	for pixel in small.pixels() {
+		let [r, g, b] = pixel.0;
+		luminance += (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0;
+		let (count, sum) = buckets.entry((r >> 4, g >> 4, b >> 4)).or_default();
+		*count += 1;
+		sum[0] += r as u32;
+		sum[1] += g as u32;
+		sum[2] += b as u32;
+	}
+
+	let mut buckets: Vec<_> = buckets.into_values().collect();
+	buckets.sort_by_key(|(count, _)| Reverse(*count));
+	let palette = buckets
+		.into_iter()
+		.take(PALETTE_SIZE)
+		.map(|(count, sum)| Color {
+			r: (sum[0] / count) as u8,
+			g: (sum[1] / count) as u8,
+			b: (sum[2] / count) as u8,
+		})
+		.collect();
+
+	Ok(ColorInfo {
+		luminance: luminance / (ANALYSIS_SIZE * ANALYSIS_SIZE) as f32,
+		palette,
+	})
+}
@@ -0,0 +1,306 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{color::ColorInfo, hash::ImageHash};
+use crate::api::{config::config::project_dir, reddit::models::download_meta::DownloadMeta};
+
+pub static HISTORY_FILENAME: &str = "history.db";
+
+/// Persistent record of downloaded images, backed by SQLite.
+///
+/// Images recorded here are never downloaded again, even if the file is removed from the
+/// download directory.
+#[derive(Clone, Debug)]
+pub struct History {
+	conn: Arc<Mutex<Connection>>,
+}
+
+impl History {
+	/// Opens history database in the project data directory.
+	pub fn open_default() -> Result<Self> {
+		let pd = project_dir();
+		let dir = pd.data_dir();
+		fs::create_dir_all(dir)
+			.with_context(|| format!("failed to create data directory on: {}", dir.display()))?;
+		Self::open(dir.join(HISTORY_FILENAME))
+	}
+
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+		let path = path.as_ref();
+		let conn = Connection::open(path)
+			.with_context(|| format!("failed to open history database on: {}", path.display()))?;
+		conn.execute_batch(
+			"CREATE TABLE IF NOT EXISTS downloads (
+				id INTEGER PRIMARY KEY,
+				post_id TEXT NOT NULL,
+				subreddit TEXT NOT NULL,
+				url TEXT NOT NULL UNIQUE,
+				profiles TEXT NOT NULL,
+				width INTEGER NOT NULL,
+				height INTEGER NOT NULL,
+				file_paths TEXT NOT NULL,
+				sha256 TEXT NOT NULL,
+				author TEXT NOT NULL,
+				title TEXT NOT NULL,
+				downloaded_at TEXT NOT NULL,
+				nsfw INTEGER NOT NULL
+			);
+			CREATE INDEX IF NOT EXISTS downloads_post_id ON downloads (post_id);
+			CREATE INDEX IF NOT EXISTS downloads_sha256 ON downloads (sha256);
+			CREATE TABLE IF NOT EXISTS perceptual_hashes (
+				path TEXT PRIMARY KEY,
+				profile TEXT NOT NULL,
+				hash INTEGER NOT NULL,
+				width INTEGER NOT NULL,
+				height INTEGER NOT NULL
+			);
+			CREATE INDEX IF NOT EXISTS perceptual_hashes_profile ON perceptual_hashes (profile);
+			CREATE TABLE IF NOT EXISTS colors (
+				url TEXT PRIMARY KEY,
+				luminance REAL NOT NULL,
+				palette TEXT NOT NULL
+			);",
+		)
+		.context("failed to prepare history database schema")?;
+		Ok(Self {
+			conn: Arc::new(Mutex::new(conn)),
+		})
+	}
+
+	/// Checks if image from given url has been downloaded before.
+	pub fn is_downloaded(&self, url: &str) -> Result<bool> {
+		let conn = self.conn.lock().unwrap();
+		let found = conn
+			.query_row(
+				"SELECT 1 FROM downloads WHERE url = ?1",
+				params![url],
+				|_| Ok(()),
+			)
+			.optional()
+			.with_context(|| format!("failed to query download history for: {}", url))?;
+		Ok(found.is_some())
+	}
+
+	/// Checks if any image of given post has been downloaded before.
+	pub fn is_post_downloaded(&self, post_id: &str) -> Result<bool> {
+		let conn = self.conn.lock().unwrap();
+		let found = conn
+			.query_row(
+				"SELECT 1 FROM downloads WHERE post_id = ?1",
+				params![post_id],
+				|_| Ok(()),
+			)
+			.optional()
+			.with_context(|| format!("failed to query download history for post: {}", post_id))?;
+		Ok(found.is_some())
+	}
+
+	/// Records a finished download. Recording the same url again replaces the previous entry.
+	pub fn record(&self, meta: &DownloadMeta, file_paths: &[PathBuf], sha256: &str) -> Result<()> {
+		let profiles = serde_json::to_string(&meta.profile)?;
+		let file_paths = serde_json::to_string(file_paths)?;
+		let conn = self.conn.lock().unwrap();
+		conn.execute(
+			"INSERT OR REPLACE INTO downloads (
+				post_id, subreddit, url, profiles, width, height, file_paths,
+				sha256, author, title, downloaded_at, nsfw
+			) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+			params![
+				meta.post_id,
+				meta.subreddit_name,
+				meta.url,
+				profiles,
+				meta.image_width,
+				meta.image_height,
+				file_paths,
+				sha256,
+				meta.author,
+				meta.title,
+				Local::now().to_rfc3339(),
+				meta.nsfw,
+			],
+		)
+		.with_context(|| format!("failed to record download history for: {}", meta.url))?;
+		Ok(())
+	}
+
+	/// Records dominant colors and luminance of the image downloaded from the url.
+	pub fn record_colors(&self, url: &str, colors: &ColorInfo) -> Result<()> {
+		let palette = serde_json::to_string(&colors.palette)?;
+		let conn = self.conn.lock().unwrap();
+		conn.execute(
+			"INSERT OR REPLACE INTO colors (url, luminance, palette) VALUES (?1, ?2, ?3)",
+			params![url, colors.luminance, palette],
+		)
+		.with_context(|| format!("failed to record colors for: {}", url))?;
+		Ok(())
+	}
+
+	/// Gets colors recorded for the image url, including images rejected by color filter.
+	pub fn find_colors(&self, url: &str) -> Result<Option<ColorInfo>> {
+		let conn = self.conn.lock().unwrap();
+		let row = conn
+			.query_row(
+				"SELECT luminance, palette FROM colors WHERE url = ?1",
+				params![url],
+				|row| Ok((row.get::<_, f32>(0)?, row.get::<_, String>(1)?)),
+			)
+			.optional()
+			.with_context(|| format!("failed to query colors for: {}", url))?;
+		Ok(match row {
+			Some((luminance, palette)) => Some(ColorInfo {
+				luminance,
+				palette: serde_json::from_str(&palette)?,
+			}),
+			None => None,
+		})
+	}
+
+	/// Lists file paths of every recorded download with the given content hash.
+	pub fn find_by_sha256(&self, sha256: &str) -> Result<Vec<PathBuf>> {
+		let conn = self.conn.lock().unwrap();
+		let mut stmt = conn.prepare("SELECT file_paths FROM downloads WHERE sha256 = ?1")?;
+		let rows = stmt.query_map(params![sha256], |row| row.get::<_, String>(0))?;
+		let mut result = Vec::new();
+		for row in rows {
+			let paths: Vec<PathBuf> = serde_json::from_str(&row?)?;
+			result.extend(paths);
+		}
+		Ok(result)
+	}
+
+	/// Finds stored images in the profile whose perceptual hash is within given distance.
+	pub fn find_similar(
+		&self,
+		profile: &str,
+		image: &ImageHash,
+		max_distance: u32,
+	) -> Result<Vec<(PathBuf, ImageHash)>> {
+		let conn = self.conn.lock().unwrap();
+		let mut stmt = conn.prepare(
+			"SELECT path, hash, width, height FROM perceptual_hashes WHERE profile = ?1",
+		)?;
+		let rows = stmt.query_map(params![profile], |row| {
+			Ok((
+				row.get::<_, String>(0)?,
+				ImageHash {
+					// sqlite only supports signed integer, hash is stored as its bit pattern.
+					hash: row.get::<_, i64>(1)? as u64,
+					width: row.get(2)?,
+					height: row.get(3)?,
+				},
+			))
+		})?;
+		let mut result = Vec::new();
+		for row in rows {
+			let (path, hash) = row?;
+			if image.distance(hash.hash) <= max_distance {
+				result.push((PathBuf::from(path), hash));
+			}
+		}
+		Ok(result)
+	}
+
+	pub fn record_perceptual_hash(
+		&self,
+		profile: &str,
+		path: &Path,
+		image: &ImageHash,
+	) -> Result<()> {
+		let conn = self.conn.lock().unwrap();
+		conn.execute(
+			"INSERT OR REPLACE INTO perceptual_hashes (path, profile, hash, width, height)
+			VALUES (?1, ?2, ?3, ?4, ?5)",
+			params![
+				path.to_string_lossy(),
+				profile,
+				image.hash as i64,
+				image.width,
+				image.height
+			],
+		)
+		.with_context(|| format!("failed to record perceptual hash for: {}", path.display()))?;
+		Ok(())
+	}
+
+	/// Removes download records that stored the file, so the image can be downloaded again.
+	/// Returns the amount of records removed.
+	pub fn forget_file(&self, path: &Path) -> Result<usize> {
+		let conn = self.conn.lock().unwrap();
+		let mut stmt = conn.prepare("SELECT url, file_paths FROM downloads")?;
+		let rows = stmt.query_map([], |row| {
+			Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+		})?;
+		let mut urls = Vec::new();
+		for row in rows {
+			let (url, file_paths) = row?;
+			let file_paths: Vec<PathBuf> = serde_json::from_str(&file_paths)?;
+			if file_paths.iter().any(|p| p == path) {
+				urls.push(url);
+			}
+		}
+		for url in &urls {
+			conn.execute("DELETE FROM downloads WHERE url = ?1", params![url])?;
+		}
+		Ok(urls.len())
+	}
+
+	/// Lists every file stored along with the file in the same download record, including the
+	/// file itself.
+	pub fn sibling_files(&self, path: &Path) -> Result<Vec<PathBuf>> {
+		let conn = self.conn.lock().unwrap();
+		let mut stmt = conn.prepare("SELECT file_paths FROM downloads")?;
+		let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+		let mut result = Vec::new();
+		for row in rows {
+			let file_paths: Vec<PathBuf> = serde_json::from_str(&row?)?;
+			if file_paths.iter().any(|p| p == path) {
+				result.extend(file_paths);
+			}
+		}
+		Ok(result)
+	}
+
+	/// Removes the file from download records, keeping the records themselves so the image is
+	/// not downloaded again.
+	pub fn remove_file_path(&self, path: &Path) -> Result<()> {
+		let conn = self.conn.lock().unwrap();
+		let mut stmt = conn.prepare("SELECT url, file_paths FROM downloads")?;
+		let rows = stmt.query_map([], |row| {
+			Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+		})?;
+		let mut updates = Vec::new();
+		for row in rows {
+			let (url, file_paths) = row?;
+			let mut file_paths: Vec<PathBuf> = serde_json::from_str(&file_paths)?;
+			let len = file_paths.len();
+			file_paths.retain(|p| p != path);
+			if file_paths.len() != len {
+				updates.push((url, serde_json::to_string(&file_paths)?));
+			}
+		}
+		for (url, file_paths) in &updates {
+			conn.execute(
+				"UPDATE downloads SET file_paths = ?1 WHERE url = ?2",
+				params![file_paths, url],
+			)?;
+		}
+		Ok(())
+	}
+
+	pub fn remove_perceptual_hash(&self, path: &Path) -> Result<()> {
+		let conn = self.conn.lock().unwrap();
+		conn.execute(
+			"DELETE FROM perceptual_hashes WHERE path = ?1",
+			params![path.to_string_lossy()],
+		)?;
+		Ok(())
+	}
+}
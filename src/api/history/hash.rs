@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::{imageops::FilterType, GenericImageView};
+use sha2::{Digest, Sha256};
+use tokio::{fs::File, io::AsyncReadExt};
+
+/// Computes hex encoded sha256 of file content.
+pub async fn sha256_file<P: AsRef<Path>>(path: P) -> Result<String> {
+	let path = path.as_ref();
+	let mut file = File::open(path)
+		.await
+		.with_context(|| format!("failed to open file {}", path.display()))?;
+	let mut hasher = Sha256::new();
+	let mut buf = vec![0; 64 * 1024];
+	loop {
+		let n = file
+			.read(&mut buf)
+			.await
+			.with_context(|| format!("failed to read file {}", path.display()))?;
+		if n == 0 {
+			break;
+		}
+		hasher.update(&buf[..n]);
+	}
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Perceptual hash of an image along with its dimension.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageHash {
+	pub hash: u64,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl ImageHash {
+	/// Counts how many bits differ between the two hashes. Lower value means more similar images.
+	pub fn distance(&self, other: u64) -> u32 {
+		(self.hash ^ other).count_ones()
+	}
+}
+
+/// Computes difference hash (dHash) of an image file.
+pub fn dhash_file<P: AsRef<Path>>(path: P) -> Result<ImageHash> {
+	let path = path.as_ref();
+	let img =
+		image::open(path).with_context(|| format!("failed to decode image {}", path.display()))?;
+	let (width, height) = img.dimensions();
+	let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+	let mut hash = 0u64;
+	for y in 0..8 {
+		for x in 0..8 {
+			hash <<= 1;
+			if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+				hash |= 1;
+			}
+		}
+	}
+	Ok(ImageHash {
+		hash,
+		width,
+		height,
+	})
+}
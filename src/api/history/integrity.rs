@@ -0,0 +1,54 @@
+use std::{
+	fs::File,
+	io::{Read, Seek, SeekFrom},
+	path::Path,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use image::io::Reader;
+use imagesize::{image_type, ImageType};
+
+/// Amount of bytes read from the end of file to look for the end of image marker.
+const TAIL_LENGTH: u64 = 64;
+
+/// Checks that the file is a complete image: the header must be readable with sane dimension,
+/// and for formats with an end marker (jpeg, png, gif) the marker must exist at the end of file.
+/// Images with data after the end marker (e.g. camera metadata) are fully decoded instead.
+///
+/// This usually reads only the head and tail of the file, so it's cheap enough to run on the whole
+/// library. Returned tuple looks like this `(width, height)`.
+pub fn check_image<P: AsRef<Path>>(path: P) -> Result<(u32, u32)> {
+	let path = path.as_ref();
+	let size = imagesize::size(path).map_err(|_| anyhow!("no readable image header"))?;
+	if size.width == 0 || size.height == 0 {
+		bail!("image has zero dimension");
+	}
+
+	let mut file =
+		File::open(path).with_context(|| format!("failed to open file {}", path.display()))?;
+	let mut head = [0; 64];
+	let n = file.read(&mut head)?;
+	let length = file.metadata()?.len();
+	file.seek(SeekFrom::Start(length.saturating_sub(TAIL_LENGTH)))?;
+	let mut tail = Vec::new();
+	file.read_to_end(&mut tail)?;
+
+	let complete = match image_type(&head[..n]) {
+		Ok(ImageType::Jpeg) => tail.windows(2).any(|w| w == [0xFF, 0xD9]),
+		Ok(ImageType::Png) => tail.ends_with(b"IEND\xAE\x42\x60\x82"),
+		Ok(ImageType::Gif) => tail.ends_with(&[0x3B]),
+		_ => true,
+	};
+	if !complete && !decodes(path) {
+		bail!("image is truncated or cannot be decoded");
+	}
+
+	Ok((size.width as u32, size.height as u32))
+}
+
+fn decodes(path: &Path) -> bool {
+	Reader::open(path)
+		.and_then(|reader| reader.with_guessed_format())
+		.map(|reader| reader.decode().is_ok())
+		.unwrap_or(false)
+}
@@ -0,0 +1,4 @@
+pub mod color;
+pub mod database;
+pub mod hash;
+pub mod integrity;
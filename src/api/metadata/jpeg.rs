@@ -0,0 +1,100 @@
+use anyhow::{bail, Result};
+
+const SOI: [u8; 2] = [0xFF, 0xD8];
+const APP0: u8 = 0xE0;
+const APP1: u8 = 0xE1;
+const XMP_NAMESPACE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Inserts XMP packet as APP1 segment. The segment is placed after existing JFIF (APP0) and
+/// Exif (APP1) segments, as image readers expect those to come first. Existing XMP segment is
+/// replaced.
+pub fn insert_xmp(data: &[u8], xmp: &str) -> Result<Vec<u8>> {
+	if !data.starts_with(&SOI) {
+		bail!("not a jpeg file");
+	}
+	// segment length includes the 2 bytes of the length itself.
+	let length = 2 + XMP_NAMESPACE.len() + xmp.len();
+	if length > u16::MAX as usize {
+		bail!("xmp metadata is too large to fit in a jpeg segment");
+	}
+
+	let mut pos = SOI.len();
+	let mut head = data[..pos].to_vec();
+	while let Some((marker, end)) = segment(data, pos) {
+		if marker != APP0 && marker != APP1 {
+			break;
+		}
+		let is_xmp = marker == APP1 && data[pos + 4..end].starts_with(XMP_NAMESPACE);
+		if !is_xmp {
+			head.extend_from_slice(&data[pos..end]);
+		}
+		pos = end;
+	}
+
+	let mut result = Vec::with_capacity(data.len() + length + 2);
+	result.extend_from_slice(&head);
+	result.extend_from_slice(&[0xFF, APP1]);
+	result.extend_from_slice(&(length as u16).to_be_bytes());
+	result.extend_from_slice(XMP_NAMESPACE);
+	result.extend_from_slice(xmp.as_bytes());
+	result.extend_from_slice(&data[pos..]);
+	Ok(result)
+}
+
+/// Reads marker segment at the position. Returns the marker and the position where the segment
+/// ends.
+fn segment(data: &[u8], pos: usize) -> Option<(u8, usize)> {
+	let header = data.get(pos..pos + 4)?;
+	if header[0] != 0xFF {
+		return None;
+	}
+	let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+	let end = pos + 2 + length;
+	if length < 2 || end > data.len() {
+		return None;
+	}
+	Some((header[1], end))
+}
+
+#[cfg(test)]
+mod tests {
+	use image::{DynamicImage, GenericImageView, ImageOutputFormat, RgbImage};
+
+	use super::*;
+
+	fn sample_jpeg() -> Vec<u8> {
+		let mut data = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::new(16, 16))
+			.write_to(&mut data, ImageOutputFormat::Jpeg(90))
+			.unwrap();
+		data
+	}
+
+	fn count(data: &[u8], needle: &[u8]) -> usize {
+		data.windows(needle.len()).filter(|w| *w == needle).count()
+	}
+
+	#[test]
+	fn inserted_xmp_still_decodes() {
+		let data = insert_xmp(&sample_jpeg(), "<x:xmpmeta>first</x:xmpmeta>").unwrap();
+		assert_eq!(count(&data, XMP_NAMESPACE), 1);
+		assert_eq!(count(&data, b"first"), 1);
+		let img = image::load_from_memory(&data).unwrap();
+		assert_eq!((img.width(), img.height()), (16, 16));
+	}
+
+	#[test]
+	fn existing_xmp_is_replaced() {
+		let data = insert_xmp(&sample_jpeg(), "<x:xmpmeta>first</x:xmpmeta>").unwrap();
+		let data = insert_xmp(&data, "<x:xmpmeta>second</x:xmpmeta>").unwrap();
+		assert_eq!(count(&data, XMP_NAMESPACE), 1);
+		assert_eq!(count(&data, b"first"), 0);
+		assert_eq!(count(&data, b"second"), 1);
+		image::load_from_memory(&data).unwrap();
+	}
+
+	#[test]
+	fn rejects_other_formats() {
+		assert!(insert_xmp(b"GIF89a", "").is_err());
+	}
+}
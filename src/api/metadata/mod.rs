@@ -0,0 +1,144 @@
+pub mod jpeg;
+pub mod png;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::api::{history::color::ColorInfo, reddit::models::download_meta::DownloadMeta};
+
+/// Post information stored along with downloaded image.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostMetadata {
+	pub post_id: String,
+	pub subreddit: String,
+	pub title: String,
+	pub author: String,
+	/// Link to the post
+	pub permalink: String,
+	/// Url the image is downloaded from
+	pub source_url: String,
+	pub nsfw: bool,
+	pub width: u32,
+	pub height: u32,
+	/// RFC 3339 formatted time
+	pub downloaded_at: String,
+	/// Dominant colors and luminance. `None` when the image cannot be decoded.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub colors: Option<ColorInfo>,
+}
+
+impl From<&DownloadMeta> for PostMetadata {
+	fn from(meta: &DownloadMeta) -> Self {
+		PostMetadata {
+			post_id: meta.post_id.to_owned(),
+			subreddit: meta.subreddit_name.to_owned(),
+			title: meta.title.to_owned(),
+			author: meta.author.to_owned(),
+			permalink: meta.post_link.to_owned(),
+			source_url: meta.url.to_owned(),
+			nsfw: meta.nsfw,
+			width: meta.image_width,
+			height: meta.image_height,
+			downloaded_at: Local::now().to_rfc3339(),
+			colors: None,
+		}
+	}
+}
+
+impl PostMetadata {
+	/// Embeds metadata into the image file in place. Only jpeg and png are supported, other
+	/// formats are left untouched.
+	///
+	/// Returns true if the file is modified.
+	pub async fn embed(&self, path: &Path, format: &str) -> Result<bool> {
+		let data = fs::read(path)
+			.await
+			.with_context(|| format!("failed to read file {}", path.display()))?;
+		let xmp = self.to_xmp();
+		let embedded = match format {
+			"jpg" => jpeg::insert_xmp(&data, &xmp)?,
+			"png" => png::insert_text(&data, &self.png_text(&xmp))?,
+			_ => return Ok(false),
+		};
+		fs::write(path, embedded)
+			.await
+			.with_context(|| format!("failed to write metadata to {}", path.display()))?;
+		Ok(true)
+	}
+
+	/// Writes metadata as json to `{image}.json` next to the image.
+	pub async fn write_sidecar(&self, image: &Path) -> Result<()> {
+		let path = sidecar_path(image);
+		let content = serde_json::to_vec_pretty(self)?;
+		fs::write(&path, content)
+			.await
+			.with_context(|| format!("failed to write metadata sidecar {}", path.display()))
+	}
+
+	fn png_text<'a>(&'a self, xmp: &'a str) -> Vec<(&'static str, &'a str)> {
+		vec![
+			("Title", &self.title),
+			("Author", &self.author),
+			("Comment", &self.permalink),
+			("Source URL", &self.source_url),
+			("XML:com.adobe.xmp", xmp),
+		]
+	}
+
+	/// Formats metadata as XMP packet. Title goes to `dc:title`, author to `dc:creator`,
+	/// permalink to `dc:source`, image url to `dc:identifier` and subreddit to `dc:subject`.
+	pub fn to_xmp(&self) -> String {
+		format!(
+			r#"<?xpacket begin="{bom}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+   <dc:title><rdf:Alt><rdf:li xml:lang="x-default">{title}</rdf:li></rdf:Alt></dc:title>
+   <dc:creator><rdf:Seq><rdf:li>{author}</rdf:li></rdf:Seq></dc:creator>
+   <dc:subject><rdf:Bag><rdf:li>r/{subreddit}</rdf:li></rdf:Bag></dc:subject>
+   <dc:source>{permalink}</dc:source>
+   <dc:identifier>{url}</dc:identifier>
+   <xmp:MetadataDate>{date}</xmp:MetadataDate>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+			bom = '\u{feff}',
+			title = escape_xml(&self.title),
+			author = escape_xml(&self.author),
+			subreddit = escape_xml(&self.subreddit),
+			permalink = escape_xml(&self.permalink),
+			url = escape_xml(&self.source_url),
+			date = escape_xml(&self.downloaded_at),
+		)
+	}
+}
+
+pub fn sidecar_path(image: &Path) -> PathBuf {
+	let mut name = image.as_os_str().to_owned();
+	name.push(".json");
+	PathBuf::from(name)
+}
+
+fn escape_xml(value: &str) -> String {
+	let mut result = String::with_capacity(value.len());
+	for c in value.chars() {
+		match c {
+			'&' => result.push_str("&amp;"),
+			'<' => result.push_str("&lt;"),
+			'>' => result.push_str("&gt;"),
+			'"' => result.push_str("&quot;"),
+			'\'' => result.push_str("&apos;"),
+			// control characters are not allowed in xml.
+			c if c.is_control() && c != '\n' && c != '\t' => {}
+			c => result.push(c),
+		}
+	}
+	result
+}
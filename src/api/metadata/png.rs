@@ -0,0 +1,126 @@
+use anyhow::{bail, Result};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Inserts uncompressed iTXt chunks with the given keyword and text pairs before the first IDAT
+/// chunk. Existing text chunks with the same keywords are replaced.
+pub fn insert_text(data: &[u8], entries: &[(&str, &str)]) -> Result<Vec<u8>> {
+	if !data.starts_with(&SIGNATURE) {
+		bail!("not a png file");
+	}
+
+	let mut result = Vec::with_capacity(data.len());
+	result.extend_from_slice(&SIGNATURE);
+	let mut pos = SIGNATURE.len();
+	let mut inserted = false;
+	while pos < data.len() {
+		let (kind, end) = match chunk(data, pos) {
+			Some(v) => v,
+			None => bail!("png file is malformed"),
+		};
+		if kind == b"IDAT" && !inserted {
+			for (keyword, text) in entries {
+				write_itxt(&mut result, keyword, text);
+			}
+			inserted = true;
+		}
+		let replaced = (kind == b"iTXt" || kind == b"tEXt" || kind == b"zTXt")
+			&& entries
+				.iter()
+				.any(|(keyword, _)| chunk_keyword(&data[pos + 8..end - 4]) == keyword.as_bytes());
+		if !replaced {
+			result.extend_from_slice(&data[pos..end]);
+		}
+		pos = end;
+	}
+	if !inserted {
+		bail!("png file has no image data");
+	}
+	Ok(result)
+}
+
+/// Reads chunk at the position. Returns the chunk type and the position where the chunk ends.
+fn chunk(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+	let header = data.get(pos..pos + 8)?;
+	let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+	// 4 bytes length + 4 bytes type + data + 4 bytes crc
+	let end = pos.checked_add(12 + length)?;
+	if end > data.len() {
+		return None;
+	}
+	Some((&header[4..8], end))
+}
+
+fn chunk_keyword(chunk_data: &[u8]) -> &[u8] {
+	match chunk_data.iter().position(|&b| b == 0) {
+		Some(n) => &chunk_data[..n],
+		None => chunk_data,
+	}
+}
+
+fn write_itxt(out: &mut Vec<u8>, keyword: &str, text: &str) {
+	let mut body = Vec::with_capacity(keyword.len() + text.len() + 5);
+	body.extend_from_slice(b"iTXt");
+	body.extend_from_slice(keyword.as_bytes());
+	// null separator, compression flag, compression method, empty language tag and empty
+	// translated keyword.
+	body.extend_from_slice(&[0, 0, 0, 0, 0]);
+	body.extend_from_slice(text.as_bytes());
+
+	out.extend_from_slice(&((body.len() - 4) as u32).to_be_bytes());
+	out.extend_from_slice(&body);
+	out.extend_from_slice(&crc32fast::hash(&body).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+	use image::{DynamicImage, GenericImageView, ImageOutputFormat, RgbImage};
+
+	use super::*;
+
+	fn sample_png() -> Vec<u8> {
+		let mut data = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::new(16, 16))
+			.write_to(&mut data, ImageOutputFormat::Png)
+			.unwrap();
+		data
+	}
+
+	/// Lists keywords of text chunks in file order.
+	fn text_keywords(data: &[u8]) -> Vec<String> {
+		let mut keywords = Vec::new();
+		let mut pos = SIGNATURE.len();
+		while let Some((kind, end)) = chunk(data, pos) {
+			if kind == b"iTXt" || kind == b"tEXt" {
+				let keyword = chunk_keyword(&data[pos + 8..end - 4]);
+				keywords.push(String::from_utf8_lossy(keyword).to_string());
+			}
+			pos = end;
+		}
+		keywords
+	}
+
+	#[test]
+	fn inserted_text_still_decodes() {
+		let data = insert_text(&sample_png(), &[("Title", "first"), ("XML", "<x/>")]).unwrap();
+		assert_eq!(text_keywords(&data), ["Title", "XML"]);
+		let img = image::load_from_memory(&data).unwrap();
+		assert_eq!((img.width(), img.height()), (16, 16));
+	}
+
+	#[test]
+	fn existing_text_is_replaced() {
+		let data = insert_text(&sample_png(), &[("Title", "first"), ("XML", "<x/>")]).unwrap();
+		let data = insert_text(&data, &[("Title", "second")]).unwrap();
+		// replaced chunk is written again right before image data.
+		assert_eq!(text_keywords(&data), ["XML", "Title"]);
+		assert!(!data.windows(5).any(|w| w == b"first"));
+		assert!(data.windows(6).any(|w| w == b"second"));
+		image::load_from_memory(&data).unwrap();
+	}
+
+	#[test]
+	fn rejects_other_formats() {
+		assert!(insert_text(b"GIF89a", &[]).is_err());
+	}
+}
@@ -1,2 +1,6 @@
 pub mod config;
+pub mod history;
+pub mod metadata;
 pub mod reddit;
+pub mod source;
+pub mod transform;
@@ -0,0 +1,131 @@
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::api::config::reddit::{OAuthFlow, RedditConfig};
+
+static ANONYMOUS_API_BASE: &str = "https://reddit.com";
+
+/// Authenticates reddit API requests using OAuth2. Access token is cached and requested again
+/// shortly before it expires.
+#[derive(Clone, Debug)]
+pub struct RedditAuth {
+	client: Arc<Client>,
+	config: RedditConfig,
+	token: Arc<Mutex<Option<Token>>>,
+}
+
+#[derive(Debug)]
+struct Token {
+	access_token: String,
+	expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+	access_token: Option<String>,
+	expires_in: Option<u64>,
+	error: Option<String>,
+}
+
+impl RedditAuth {
+	pub fn new(client: Arc<Client>, config: RedditConfig) -> Self {
+		Self {
+			client,
+			config,
+			token: Arc::new(Mutex::new(None)),
+		}
+	}
+
+	pub fn enabled(&self) -> bool {
+		self.config.flow != OAuthFlow::Anonymous
+	}
+
+	/// Base url for reddit API calls, e.g. `https://oauth.reddit.com`.
+	pub fn api_base(&self) -> &str {
+		if self.enabled() {
+			self.config.api_base.trim_end_matches('/')
+		} else {
+			ANONYMOUS_API_BASE
+		}
+	}
+
+	/// Adds bearer token to the request. Request is returned as is on anonymous access.
+	pub async fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder> {
+		if !self.enabled() {
+			return Ok(request);
+		}
+		Ok(request.bearer_auth(self.access_token().await?))
+	}
+
+	async fn access_token(&self) -> Result<String> {
+		let mut token = self.token.lock().await;
+		if let Some(token) = token.as_ref() {
+			if token.expires_at > Instant::now() {
+				return Ok(token.access_token.to_owned());
+			}
+		}
+		let new_token = self.request_token().await?;
+		let access_token = new_token.access_token.to_owned();
+		*token = Some(new_token);
+		Ok(access_token)
+	}
+
+	async fn request_token(&self) -> Result<Token> {
+		let form: Vec<(&str, &str)> = match self.config.flow {
+			OAuthFlow::Script => vec![
+				("grant_type", "password"),
+				("username", &self.config.username),
+				("password", &self.config.password),
+			],
+			OAuthFlow::Installed => vec![
+				(
+					"grant_type",
+					"https://oauth.reddit.com/grant_types/installed_client",
+				),
+				("device_id", &self.config.device_id),
+			],
+			OAuthFlow::Anonymous => bail!("anonymous access does not need access token"),
+		};
+		let resp = self
+			.client
+			.post(&self.config.token_url)
+			.basic_auth(&self.config.client_id, Some(&self.config.client_secret))
+			.form(&form)
+			.send()
+			.await
+			.with_context(|| {
+				format!(
+					"failed to open connection to request access token from: {}",
+					self.config.token_url
+				)
+			})?;
+		let status = resp.status();
+		let body: TokenResponse = resp.json().await.with_context(|| {
+			format!(
+				"failed to deserialize json body from: {}",
+				self.config.token_url
+			)
+		})?;
+		let access_token = match (body.access_token, body.error) {
+			(Some(token), _) if status.is_success() => token,
+			(_, error) => bail!(
+				"reddit rejected access token request with [{}]: {}",
+				status.as_u16(),
+				error.unwrap_or_else(|| "unknown error".to_string())
+			),
+		};
+		// renew a minute early so a token never expires in the middle of a run.
+		let expires_in = body.expires_in.unwrap_or(3600).saturating_sub(60);
+		Ok(Token {
+			access_token,
+			expires_at: Instant::now() + Duration::from_secs(expires_in),
+		})
+	}
+}
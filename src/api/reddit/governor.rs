@@ -0,0 +1,165 @@
+use std::{
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use reqwest::{
+	header::{HeaderMap, RETRY_AFTER},
+	RequestBuilder, Response, StatusCode,
+};
+use tokio::time::sleep;
+use tokio_retry::strategy::jitter;
+
+const MAX_RETRIES: u32 = 5;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Shared request throttle for reddit API calls.
+///
+/// Reads `X-Ratelimit-Remaining` and `X-Ratelimit-Reset` headers to pause before the limit is
+/// exhausted, and backs off exponentially on 429 and 5xx responses. A 429 pauses every request
+/// sharing the governor, not just the one that got it.
+///
+/// Only reddit API calls should go through the governor. Media hosts do not report the rate
+/// limit headers, use [`send_with_retry`] for them.
+#[derive(Clone, Debug, Default)]
+pub struct RateGovernor {
+	state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	/// Requests left in the current window, as reported by reddit minus requests sent since.
+	remaining: Option<f64>,
+	reset_at: Option<Instant>,
+	blocked_until: Option<Instant>,
+}
+
+impl RateGovernor {
+	/// Sends the request, waiting for rate limit and retrying on 429, 5xx and connection errors.
+	///
+	/// The last response is returned as is when retries are exhausted.
+	pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+		retry(request, Some(self)).await
+	}
+
+	/// Sleeps until the governor allows another request, then reserves one from the window.
+	async fn wait_turn(&self) {
+		loop {
+			let wait = {
+				let mut state = self.state.lock().unwrap();
+				let now = Instant::now();
+				match (state.blocked_until, state.remaining, state.reset_at) {
+					(Some(until), _, _) if until > now => Some(until - now),
+					(_, Some(remaining), Some(reset_at)) if remaining < 1.0 && reset_at > now => {
+						Some(reset_at - now)
+					}
+					_ => {
+						state.blocked_until = None;
+						if let Some(reset_at) = state.reset_at {
+							if reset_at <= now {
+								state.remaining = None;
+								state.reset_at = None;
+							}
+						}
+						if let Some(remaining) = state.remaining.as_mut() {
+							*remaining -= 1.0;
+						}
+						None
+					}
+				}
+			};
+			match wait {
+				Some(duration) => sleep(duration).await,
+				None => return,
+			}
+		}
+	}
+
+	fn update(&self, headers: &HeaderMap) {
+		let remaining = header_f64(headers, "x-ratelimit-remaining");
+		let reset = header_f64(headers, "x-ratelimit-reset");
+		let reset_at = reset
+			.and_then(|reset| Duration::try_from_secs_f64(reset.max(0.0)).ok())
+			.and_then(|reset| Instant::now().checked_add(reset));
+		if let (Some(remaining), Some(reset_at)) = (remaining, reset_at) {
+			let mut state = self.state.lock().unwrap();
+			state.remaining = Some(remaining);
+			state.reset_at = Some(reset_at);
+		}
+	}
+
+	fn block_for(&self, duration: Duration) {
+		let mut state = self.state.lock().unwrap();
+		let until = Instant::now() + duration;
+		if state.blocked_until.map(|b| b < until).unwrap_or(true) {
+			state.blocked_until = Some(until);
+		}
+	}
+}
+
+/// Sends the request, retrying on 429, 5xx and connection errors without touching any governor.
+///
+/// The last response is returned as is when retries are exhausted.
+pub async fn send_with_retry(request: RequestBuilder) -> Result<Response> {
+	retry(request, None).await
+}
+
+async fn retry(request: RequestBuilder, governor: Option<&RateGovernor>) -> Result<Response> {
+	let mut attempt = 0;
+	loop {
+		if let Some(governor) = governor {
+			governor.wait_turn().await;
+		}
+		let req = request
+			.try_clone()
+			.context("request with streaming body cannot be retried")?;
+		match req.send().await {
+			Ok(resp) => {
+				if let Some(governor) = governor {
+					governor.update(resp.headers());
+				}
+				let status = resp.status();
+				let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+				if !retryable || attempt >= MAX_RETRIES {
+					return Ok(resp);
+				}
+				let delay = retry_after(resp.headers()).unwrap_or_else(|| backoff(attempt));
+				match governor {
+					Some(governor) if status == StatusCode::TOO_MANY_REQUESTS => {
+						governor.block_for(delay)
+					}
+					_ => sleep(delay).await,
+				}
+			}
+			Err(err) => {
+				if attempt >= MAX_RETRIES {
+					return Err(err.into());
+				}
+				sleep(backoff(attempt)).await;
+			}
+		}
+		attempt += 1;
+	}
+}
+
+fn header_f64(headers: &HeaderMap, name: &str) -> Option<f64> {
+	headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Reads `Retry-After` header in seconds. HTTP date format is not supported.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+	let secs = header_f64(headers, RETRY_AFTER.as_str())?;
+	// out of range values, e.g. `inf`, are ignored.
+	let delay = Duration::try_from_secs_f64(secs.max(0.0)).ok()?;
+	Some(delay.min(MAX_BACKOFF))
+}
+
+fn backoff(attempt: u32) -> Duration {
+	let delay = BASE_BACKOFF
+		.checked_mul(2u32.saturating_pow(attempt))
+		.unwrap_or(MAX_BACKOFF)
+		.min(MAX_BACKOFF);
+	jitter(delay)
+}
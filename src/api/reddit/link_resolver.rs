@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use reqwest::{header::AUTHORIZATION, Client, Url};
+
+use crate::api::config::{config::Config, imgur::ImgurConfig};
+
+use super::models::{
+	imgur::{ImgurImage, ImgurResponse},
+	listing::{ChildrenData, Listing, PostImage},
+};
+
+/// Rewrites links to third party image hosts in a listing into direct image links, so the
+/// posts can be processed like any other image post.
+#[derive(Clone, Debug)]
+pub struct LinkResolver {
+	client: Arc<Client>,
+	imgur: ImgurConfig,
+}
+
+enum ImgurLink {
+	Image(String),
+	Album(String),
+}
+
+impl LinkResolver {
+	pub fn new(client: Arc<Client>, config: &Config) -> Self {
+		Self {
+			client,
+			imgur: config.imgur.clone(),
+		}
+	}
+
+	/// Resolves links of every post in the listing. Posts whose link fails to resolve are left
+	/// as is. Albums cost an API call each, so they are only resolved for posts `wanted` accepts.
+	pub async fn resolve<F: Fn(&ChildrenData) -> bool>(&self, listing: &mut Listing, wanted: F) {
+		for children in listing.data.children.iter_mut() {
+			let data = &mut children.data;
+			match LinkResolver::parse_imgur_link(&data.url) {
+				Some(ImgurLink::Image(id)) => {
+					// imgur serves the image in its actual format whatever the extension, the file
+					// name is corrected once the content is downloaded.
+					data.url = format!("https://i.imgur.com/{}.jpg", id);
+				}
+				Some(ImgurLink::Album(id)) if wanted(data) => {
+					data.resolved_images = self.imgur_album(&id, data).await.ok();
+				}
+				_ => {}
+			}
+		}
+	}
+
+	fn parse_imgur_link(link: &str) -> Option<ImgurLink> {
+		let url = Url::parse(link).ok()?;
+		match url.host_str()? {
+			"imgur.com" | "www.imgur.com" | "m.imgur.com" => {}
+			_ => return None,
+		}
+		let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+		match segments.as_slice() {
+			["a", id] | ["gallery", id] => Some(ImgurLink::Album(id.to_string())),
+			[id] => {
+				let id = id.split('.').next().unwrap();
+				Some(ImgurLink::Image(id.to_string()))
+			}
+			_ => None,
+		}
+	}
+
+	/// Fetches album images from imgur API. Images are named like gallery images.
+	async fn imgur_album(&self, album_id: &str, data: &ChildrenData) -> Result<Vec<PostImage>> {
+		if self.imgur.client_id.is_empty() {
+			bail!("imgur client id is not configured")
+		}
+		let url = format!(
+			"{}/3/album/{}/images",
+			self.imgur.api_base.trim_end_matches('/'),
+			album_id
+		);
+		let resp = self
+			.client
+			.get(&url)
+			.header(AUTHORIZATION, format!("Client-ID {}", self.imgur.client_id))
+			.send()
+			.await
+			.with_context(|| format!("failed to open connection to imgur album: {}", url))?;
+		if !resp.status().is_success() {
+			bail!(
+				"imgur album {} gives [{}] status code",
+				url,
+				resp.status().as_u16()
+			)
+		}
+		let body: ImgurResponse<Vec<ImgurImage>> = resp
+			.json()
+			.await
+			.with_context(|| format!("failed to deserialize json body from: {}", url))?;
+
+		let mut result = Vec::new();
+		for (i, image) in body.data.iter().enumerate() {
+			if image.animated {
+				continue;
+			}
+			let ext = match image.extension() {
+				Some(ext) => ext,
+				None => continue,
+			};
+			result.push(PostImage {
+				url: image.link.to_owned(),
+				filename: PostImage::position_filename(&data.id, i, ext),
+				width: image.width,
+				height: image.height,
+			});
+		}
+		Ok(result)
+	}
+}
@@ -1,2 +1,6 @@
+pub mod auth;
+pub mod governor;
+pub mod link_resolver;
 pub mod models;
 pub mod repository;
+pub mod source;
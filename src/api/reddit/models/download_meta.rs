@@ -1,12 +1,18 @@
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
+use chrono::{Local, TimeZone};
 use pad::PadStr;
+use tokio::fs;
 
-use crate::api::config::configuration::Configuration;
+use crate::api::config::{
+	config::{Config, Settings},
+	configuration::{normalize_format, Configuration, DownloadSize},
+};
 
 use super::download_status::DownloadStatus;
 
 pub struct DownloadMeta {
+	pub post_id: String,
 	pub url: String,
 	pub subreddit_name: String,
 	pub image_height: u32,
@@ -16,9 +22,23 @@ pub struct DownloadMeta {
 	pub filename: String,
 	pub title: String,
 	pub author: String,
+	/// Unix timestamp (in seconds) of when the image is posted
+	pub created: i64,
+	/// File size in bytes. `None` until known from the server or the downloaded file.
+	pub file_size: Option<u64>,
+	pub flair: Option<String>,
+	/// Post statistics. `None` when the source has no such information.
+	pub score: Option<i64>,
+	pub upvote_ratio: Option<f32>,
+	pub num_comments: Option<u32>,
+	/// Reason the image is rejected. Rejected images are reported instead of downloaded.
+	pub rejected: Option<String>,
 	pub profile: Vec<String>,
 }
 
+/// Maximum length (in characters) of a single placeholder value in file name.
+const MAX_PLACEHOLDER_LENGTH: usize = 100;
+
 impl DownloadMeta {
 	pub fn get_file_location<P: AsRef<Path>>(&self, base_location: P) -> PathBuf {
 		base_location
@@ -28,8 +48,180 @@ impl DownloadMeta {
 			.to_path_buf()
 	}
 
+	/// Location of the image in the profile directory, rendered from the file naming template of
+	/// the profile.
+	pub fn file_location(&self, config: &Config, profile: &str) -> PathBuf {
+		let template = config.filename_template(profile);
+		config
+			.path
+			.join(profile)
+			.join(self.render_filename(template))
+	}
+
+	/// Checks if the image is already stored in the profile. Only works when the file naming
+	/// template contains the original file name, since other templates may render the same
+	/// location for different images.
+	pub async fn is_stored(&self, config: &Config, profile: &str) -> bool {
+		let template = config.filename_template(profile);
+		if !template.contains("{filename}") && !template.contains("{name}") {
+			return false;
+		}
+		fs::metadata(self.file_location(config, profile))
+			.await
+			.is_ok()
+	}
+
+	/// Renders file location relative to the profile directory from the template.
+	///
+	/// Supported placeholders are `{subreddit}`, `{post_id}`, `{title}`, `{author}`, `{width}`,
+	/// `{height}`, `{date}`, `{filename}` (original file name), `{name}` (original file name
+	/// without extension) and `{ext}`. Placeholder values are sanitized so they are safe to use
+	/// as file name, while `/` in the template itself creates directories. The extension is appended
+	/// when the template does not end with it.
+	pub fn render_filename(&self, template: &str) -> PathBuf {
+		let (name, ext) = self
+			.filename
+			.rsplit_once('.')
+			.unwrap_or((&self.filename, ""));
+		let date = Local
+			.timestamp(self.created, 0)
+			.format("%Y-%m-%d")
+			.to_string();
+		let width = self.image_width.to_string();
+		let height = self.image_height.to_string();
+		let value = |placeholder: &str| -> Option<&str> {
+			Some(match placeholder {
+				"subreddit" => &self.subreddit_name,
+				"post_id" => &self.post_id,
+				"title" => &self.title,
+				"author" => &self.author,
+				"width" => &width,
+				"height" => &height,
+				"date" => &date,
+				"filename" => &self.filename,
+				"name" => name,
+				"ext" => ext,
+				_ => return None,
+			})
+		};
+
+		// filled in one pass, so placeholders inside the values are kept as is.
+		let mut rendered = String::with_capacity(template.len());
+		let mut rest = template;
+		while let Some(start) = rest.find('{') {
+			rendered.push_str(&rest[..start]);
+			rest = &rest[start..];
+			let filled = rest
+				.find('}')
+				.and_then(|end| Some((value(&rest[1..end])?, end)));
+			match filled {
+				Some((value, end)) => {
+					rendered.push_str(&sanitize(value));
+					rest = &rest[end + 1..];
+				}
+				None => {
+					rendered.push('{');
+					rest = &rest[1..];
+				}
+			}
+		}
+		rendered.push_str(rest);
+
+		// only plain components are allowed, so the file never ends up outside profile directory.
+		let path: PathBuf = Path::new(&rendered)
+			.components()
+			.filter(|c| matches!(c, Component::Normal(_)))
+			.collect();
+		if path.as_os_str().is_empty() {
+			return self.get_file_location("");
+		}
+		let has_ext = path
+			.extension()
+			.map(|e| normalize_format(&e.to_string_lossy()) == normalize_format(ext))
+			.unwrap_or(false);
+		if has_ext || ext.is_empty() {
+			return path;
+		}
+		let mut path = path.into_os_string();
+		path.push(".");
+		path.push(ext);
+		PathBuf::from(path)
+	}
+
+	/// Changes the file name extension to the actual image format, so `{ext}` and the stored file
+	/// match the content. Aliases of the format (e.g. `jpeg` for `jpg`) are kept.
+	pub fn set_format(&mut self, format: &str) {
+		let name = match self.filename.rsplit_once('.') {
+			Some((_, ext)) if normalize_format(ext) == format => return,
+			Some((name, _)) => name,
+			None => &self.filename,
+		};
+		self.filename = format!("{}.{}", name, format);
+	}
+
+	/// Adds every profile the image passes checks for. Returns false if no profile fits.
+	pub fn assign_profiles(&mut self, settings: &Settings) -> bool {
+		for (profile, setting) in settings.iter() {
+			if self.passed_checks(setting) {
+				self.profile.push(profile.to_owned());
+			}
+		}
+		!self.profile.is_empty()
+	}
+
+	/// Finds the smallest size that satisfies the download size of every assigned profile.
+	/// Returns `None` if any of them wants the source image.
+	pub fn download_target(&self, settings: &Settings) -> Option<(u32, u32)> {
+		let mut target = (0, 0);
+		for profile in &self.profile {
+			match settings.get(profile)?.download_size {
+				DownloadSize::Source => return None,
+				DownloadSize::AtLeast(res) => {
+					target = (target.0.max(res.width), target.1.max(res.height));
+				}
+			}
+		}
+		Some(target)
+	}
+
 	pub fn passed_checks(&self, config: &Configuration) -> bool {
-		self.passed_aspect_ratio(config) && self.passed_mininum_size(config)
+		self.passed_image_checks(config) && config.filter.check(self).is_ok()
+	}
+
+	/// Finds the reason the post is rejected, when there are profiles the image fits but every one
+	/// of them filters the post out.
+	pub fn filter_rejection(&self, settings: &Settings) -> Option<String> {
+		let mut reasons = Vec::new();
+		for (profile, setting) in settings.iter() {
+			if !self.passed_image_checks(setting) {
+				continue;
+			}
+			match setting.filter.check(self) {
+				Ok(_) => return None,
+				Err(reason) => reasons.push(format!("{}: {}", profile, reason)),
+			}
+		}
+		if reasons.is_empty() {
+			return None;
+		}
+		Some(reasons.join(", "))
+	}
+
+	fn passed_image_checks(&self, config: &Configuration) -> bool {
+		self.passed_aspect_ratio(config)
+			&& self.passed_mininum_size(config)
+			&& self.passed_maximum_size(config)
+			&& self.passed_exact_resolution(config)
+			&& self.passed_orientation(config)
+			&& self.passed_file_size(config)
+			&& self.passed_format(config)
+	}
+
+	pub fn passed_format(&self, config: &Configuration) -> bool {
+		match self.filename.rsplit_once('.') {
+			Some((_, ext)) => config.accepts_format(ext),
+			None => false,
+		}
 	}
 
 	pub fn passed_aspect_ratio(&self, config: &Configuration) -> bool {
@@ -51,6 +243,37 @@ impl DownloadMeta {
 			&& self.image_height >= config.minimum_size.height
 	}
 
+	pub fn passed_maximum_size(&self, config: &Configuration) -> bool {
+		if !config.maximum_size.enable {
+			return true;
+		}
+		self.image_width <= config.maximum_size.width
+			&& self.image_height <= config.maximum_size.height
+	}
+
+	pub fn passed_exact_resolution(&self, config: &Configuration) -> bool {
+		if !config.exact_resolution.enable {
+			return true;
+		}
+		config
+			.exact_resolution
+			.matches(self.image_width, self.image_height)
+	}
+
+	pub fn passed_orientation(&self, config: &Configuration) -> bool {
+		config
+			.orientation
+			.accepts(self.image_width, self.image_height)
+	}
+
+	/// Always passes when the file size is not known yet.
+	pub fn passed_file_size(&self, config: &Configuration) -> bool {
+		match self.file_size {
+			Some(size) => config.file_size.accepts(size),
+			None => true,
+		}
+	}
+
 	pub fn padded_subreddit_name(&self) -> String {
 		("[".to_string() + &self.subreddit_name + "]").pad_to_width(23)
 	}
@@ -69,3 +292,21 @@ impl DownloadMeta {
 		)
 	}
 }
+
+/// Replaces characters that are not safe for file names and trims the value to reasonable length.
+fn sanitize(value: &str) -> String {
+	let value: String = value
+		.chars()
+		.map(|c| match c {
+			'/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+			c if c.is_control() => '_',
+			c => c,
+		})
+		.take(MAX_PLACEHOLDER_LENGTH)
+		.collect();
+	let value = value.trim_matches(|c: char| c == '.' || c.is_whitespace());
+	if value.is_empty() {
+		return "_".to_string();
+	}
+	value.to_string()
+}
@@ -10,6 +10,10 @@ pub struct DownloadStatus {
 	pub chunk_length: u64,
 	pub finished: bool,
 	pub error: Option<String>,
+	/// Reason the image is rejected by every profile
+	pub rejected: Option<String>,
+	/// Check that could not be done on the image. The image is still stored.
+	pub warning: Option<String>,
 	pub url: String,
 }
 
@@ -29,9 +33,23 @@ impl DownloadStatus {
 			url,
 			finished: false,
 			error: None,
+			rejected: None,
+			warning: None,
 		}
 	}
 
+	/// Gives rejection reason to self and set to finished
+	pub fn with_rejection(mut self, reason: String) -> Self {
+		self.rejected = Some(reason);
+		self.set_finished()
+	}
+
+	/// Gives warning to self. Does not finish the download
+	pub fn with_warning(mut self, warning: String) -> Self {
+		self.warning = Some(warning);
+		self
+	}
+
 	/// Givem error to self and set to finished
 	pub fn with_error(mut self, error: String) -> Self {
 		self.error = Some(error);
@@ -64,6 +82,8 @@ impl From<DownloadStatus> for ProtoDownloadStatus {
 			chunk_length: ds.chunk_length,
 			finished: ds.finished,
 			error: ds.error,
+			rejected: ds.rejected,
+			warning: ds.warning,
 		}
 	}
 }
@@ -0,0 +1,25 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ImgurResponse<T> {
+	pub data: T,
+	pub success: bool,
+	pub status: u16,
+}
+
+#[derive(Deserialize)]
+pub struct ImgurImage {
+	pub id: String,
+	pub link: String,
+	pub width: u32,
+	pub height: u32,
+	#[serde(default)]
+	pub animated: bool,
+}
+
+impl ImgurImage {
+	pub fn extension(&self) -> Option<&str> {
+		let last = self.link.rsplit('/').next()?;
+		Some(last.rsplit_once('.')?.1)
+	}
+}
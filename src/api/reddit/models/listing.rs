@@ -1,6 +1,11 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::Deserialize;
 
-use crate::api::config::config::Config;
+use crate::api::config::{
+	config::{Config, Settings},
+	policy::CrosspostPolicy,
+};
 
 use super::download_meta::DownloadMeta;
 
@@ -12,12 +17,11 @@ pub struct Listing {
 impl Listing {
 	pub fn into_download_metas(self, config: &Config) -> Vec<DownloadMeta> {
 		let mut result: Vec<DownloadMeta> = Vec::new();
+		let mut seen = HashSet::new();
+		// profiles of each subreddit, after its profile restriction.
+		let mut sub_settings: HashMap<String, Settings> = HashMap::new();
 		for children in self.data.children.into_iter() {
-			let data = children.data;
-			if data.is_video {
-				continue;
-			}
-
+			let mut data = children.data;
 			let sub_name = &data.subreddit;
 			let sub = config
 				.subreddits
@@ -25,74 +29,143 @@ impl Listing {
 				.unwrap_or_else(|| {
 					panic!("subreddit '{}' does not exist in configuration", sub_name)
 				});
+			let settings = sub_settings
+				.entry(sub_name.to_lowercase())
+				.or_insert_with(|| {
+					config
+						.settings
+						.iter()
+						.filter(|(profile, _)| sub.accepts_profile(profile))
+						.map(|(profile, setting)| (profile.to_owned(), setting.clone()))
+						.collect()
+				});
 
-			if data.over_18 && !sub.nsfw {
+			if !sub.policy.accepts(&data) {
+				continue;
+			}
+			let parent = data
+				.crosspost_parent_list
+				.take()
+				.and_then(|list| list.into_iter().next());
+			if let Some(mut parent) = parent {
+				match sub.policy.crosspost {
+					CrosspostPolicy::Skip => continue,
+					CrosspostPolicy::Allow => {}
+					CrosspostPolicy::Resolve => {
+						if !sub.policy.accepts(&parent) {
+							continue;
+						}
+						// images are still stored under the subscribed subreddit.
+						parent.subreddit = data.subreddit;
+						data = parent;
+					}
+				}
+			}
+			// the same original post may be crossposted several times in one listing.
+			if !seen.insert(data.id.to_owned()) {
 				continue;
 			}
 
-			let filename = match Listing::get_filename_from_url(&data.url) {
-				Some(name) => name,
-				None => continue,
-			};
-
-			let (width, height) = match data.get_image_size() {
-				Some(s) => s,
-				// return (1, 1) to prevent panic divide by 0
-				None => (1, 1),
-			};
-
-			let mut meta = DownloadMeta {
-				subreddit_name: data.subreddit,
-				post_link: format!("https://reddit.com{}", data.permalink),
-				image_width: width,
-				image_height: height,
-				filename,
-				url: data.url,
-				nsfw: data.over_18,
-				title: data.title,
-				author: data.author,
-				profile: Vec::new(),
-			};
+			if data.is_video {
+				continue;
+			}
 
-			if sub.download_first {
-				result.push(meta);
+			if data.over_18 && !sub.nsfw {
 				continue;
 			}
 
-			let mut should_download = false;
+			for image in data.post_images(config) {
+				let mut meta = DownloadMeta {
+					post_id: data.id.to_owned(),
+					subreddit_name: data.subreddit.to_owned(),
+					post_link: format!("https://reddit.com{}", data.permalink),
+					image_width: image.width,
+					image_height: image.height,
+					filename: image.filename,
+					url: image.url,
+					nsfw: data.over_18,
+					title: data.title.to_owned(),
+					author: data.author.to_owned(),
+					created: data.created as i64,
+					file_size: None,
+					flair: data.link_flair_text.to_owned(),
+					score: data.score,
+					upvote_ratio: data.upvote_ratio,
+					num_comments: data.num_comments,
+					rejected: None,
+					profile: Vec::new(),
+				};
 
-			for (profile, setting) in config.settings.iter() {
-				if !meta.passed_checks(setting) {
+				if let Err(reason) = sub.filter.check(&meta) {
+					meta.rejected = Some(reason);
+					result.push(meta);
 					continue;
 				}
-				meta.profile.push(profile.to_owned());
-				should_download = true;
-			}
 
-			if !should_download {
-				continue;
-			}
+				if sub.download_first {
+					result.push(meta);
+					continue;
+				}
+
+				if !meta.assign_profiles(settings) {
+					meta.rejected = meta.filter_rejection(settings);
+					if meta.rejected.is_some() {
+						result.push(meta);
+					}
+					continue;
+				}
+				if let Some((width, height)) = meta.download_target(&config.settings) {
+					if let Some(preview) = data.preview_at_least(width, height) {
+						meta.url = unescape_url(&preview.url);
+						meta.image_width = preview.width as u32;
+						meta.image_height = preview.height as u32;
+					}
+				}
 
-			result.push(meta);
+				result.push(meta);
+			}
 		}
 		result
 	}
 
-	fn get_filename_from_url(url: &str) -> Option<String> {
+	fn get_filename_from_url(url: &str, config: &Config) -> Option<String> {
 		let s: String = url.split("/").last().unwrap().split("?").take(1).collect();
 		if let Some(ext) = s.split(".").last() {
-			if ext.len() > 3 || (ext != "jpg" && ext != "png") {
+			if !Listing::is_supported_extension(ext, config) {
 				return None;
 			}
 			return Some(s);
 		}
 		None
 	}
+
+	fn is_supported_extension(ext: &str, config: &Config) -> bool {
+		config.accepts_format(ext)
+	}
+}
+
+/// A single downloadable image inside a post. Gallery posts produce one per gallery item.
+#[derive(Clone)]
+pub struct PostImage {
+	pub url: String,
+	pub filename: String,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl PostImage {
+	/// File name of an image in a post with several images, e.g. galleries and albums. Formatted
+	/// as `{post_id}_{position}.{ext}`, where position is `index + 1`.
+	pub fn position_filename(post_id: &str, index: usize, ext: &str) -> String {
+		format!("{}_{}.{}", post_id, index + 1, ext)
+	}
 }
 
 #[derive(Deserialize)]
 pub struct Data {
 	pub children: Vec<Children>,
+	/// Cursor to the next page of the listing. `None` when there is no more page.
+	pub after: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -115,9 +188,107 @@ pub struct ChildrenData {
 	pub url: String,
 	pub is_video: bool,
 	pub is_gallery: Option<bool>,
+	pub link_flair_text: Option<String>,
+	pub score: Option<i64>,
+	pub upvote_ratio: Option<f32>,
+	pub num_comments: Option<u32>,
+	pub spoiler: Option<bool>,
+	pub removed_by_category: Option<String>,
+	/// Original post of a crosspost. Reddit puts the original post as the only item.
+	pub crosspost_parent_list: Option<Vec<ChildrenData>>,
+	pub media_metadata: Option<HashMap<String, MediaMetadata>>,
+	pub gallery_data: Option<GalleryData>,
+	/// Images resolved from the post link by the link resolver, e.g. imgur albums.
+	#[serde(skip)]
+	pub resolved_images: Option<Vec<PostImage>>,
 }
 
 impl ChildrenData {
+	/// Lists the images this post contains. Returns empty vec if the post has no supported image.
+	pub fn post_images(&self, config: &Config) -> Vec<PostImage> {
+		if let Some(images) = &self.resolved_images {
+			return images
+				.iter()
+				.filter(|image| match image.filename.rsplit_once('.') {
+					Some((_, ext)) => Listing::is_supported_extension(ext, config),
+					None => false,
+				})
+				.cloned()
+				.collect();
+		}
+
+		if self.is_gallery.unwrap_or(false) {
+			return self.gallery_images(config);
+		}
+
+		let filename = match Listing::get_filename_from_url(&self.url, config) {
+			Some(name) => name,
+			None => return Vec::new(),
+		};
+
+		let (width, height) = match self.get_image_size() {
+			Some(s) => s,
+			// return (1, 1) to prevent panic divide by 0
+			None => (1, 1),
+		};
+
+		vec![PostImage {
+			url: self.url.to_owned(),
+			filename,
+			width,
+			height,
+		}]
+	}
+
+	/// Expands gallery items into images, ordered by their position in the gallery.
+	fn gallery_images(&self, config: &Config) -> Vec<PostImage> {
+		let (gallery, metadata) = match (&self.gallery_data, &self.media_metadata) {
+			(Some(gallery), Some(metadata)) => (gallery, metadata),
+			_ => return Vec::new(),
+		};
+
+		let mut result = Vec::new();
+		for (i, item) in gallery.items.iter().enumerate() {
+			let media = match metadata.get(&item.media_id) {
+				Some(media) => media,
+				None => continue,
+			};
+			let ext = match media.extension() {
+				Some(ext) if Listing::is_supported_extension(ext, config) => ext,
+				_ => continue,
+			};
+			let (width, height) = match &media.s {
+				Some(s) => (s.x, s.y),
+				None => (1, 1),
+			};
+			result.push(PostImage {
+				url: format!("https://i.redd.it/{}.{}", item.media_id, ext),
+				filename: PostImage::position_filename(&self.id, i, ext),
+				width,
+				height,
+			});
+		}
+		result
+	}
+
+	/// Finds the smallest preview of the image with at least the given width and height. Only
+	/// posts linking a single image have previews of the same image.
+	///
+	/// Returns `None` when there is no smaller preview that is large enough.
+	pub fn preview_at_least(&self, width: u32, height: u32) -> Option<&Resolution> {
+		if self.resolved_images.is_some() || self.is_gallery.unwrap_or(false) {
+			return None;
+		}
+		let image = self.preview.as_ref()?.images.first()?;
+		let source = &image.source;
+		image
+			.resolutions
+			.iter()
+			.filter(|res| res.width >= width as i64 && res.height >= height as i64)
+			.filter(|res| res.width < source.width as i64 || res.height < source.height as i64)
+			.min_by_key(|res| res.width * res.height)
+	}
+
 	/// Returned tuple looks like this `(width, height)`
 	pub fn get_image_size(&self) -> Option<(u32, u32)> {
 		if let Some(preview) = &self.preview {
@@ -127,6 +298,50 @@ impl ChildrenData {
 	}
 }
 
+/// Reddit escapes `&` in preview urls as `&amp;`.
+pub fn unescape_url(url: &str) -> String {
+	url.replace("&amp;", "&")
+}
+
+#[derive(Deserialize)]
+pub struct GalleryData {
+	pub items: Vec<GalleryItem>,
+}
+
+#[derive(Deserialize)]
+pub struct GalleryItem {
+	pub media_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct MediaMetadata {
+	pub status: String,
+	/// Mime type, e.g. `image/jpg`
+	pub m: Option<String>,
+	/// Source image
+	pub s: Option<MediaSource>,
+}
+
+impl MediaMetadata {
+	/// Gets file extension from the mime type. Returns `None` if media is not a valid image.
+	pub fn extension(&self) -> Option<&str> {
+		if self.status != "valid" {
+			return None;
+		}
+		let mime = self.m.as_deref()?;
+		match mime.strip_prefix("image/")? {
+			"jpeg" => Some("jpg"),
+			ext => Some(ext),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+pub struct MediaSource {
+	pub x: u32,
+	pub y: u32,
+}
+
 #[derive(Deserialize)]
 pub struct MediaEmbed {}
 
@@ -1,4 +1,6 @@
 pub mod download_meta;
 pub mod download_status;
 pub mod error;
+pub mod imgur;
 pub mod listing;
+pub mod partial_download;
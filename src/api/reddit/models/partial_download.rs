@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use reqwest::{
+	header::{HeaderMap, ACCEPT_RANGES, CONTENT_RANGE, ETAG, LAST_MODIFIED},
+	Response,
+};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+/// Sidecar state of a partially downloaded temp file, used to resume the download with a
+/// `Range` request.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PartialDownload {
+	pub url: String,
+	pub etag: Option<String>,
+	pub last_modified: Option<String>,
+	/// Full length of the file
+	pub total_length: u64,
+}
+
+impl PartialDownload {
+	/// Creates state from a full (non range) response. Returns `None` if the server does not
+	/// support resuming the download.
+	pub fn from_response(url: &str, resp: &Response) -> Option<Self> {
+		let headers = resp.headers();
+		let accept_ranges = header_str(headers, ACCEPT_RANGES.as_str())?;
+		if !accept_ranges.eq_ignore_ascii_case("bytes") {
+			return None;
+		}
+		let state = PartialDownload {
+			url: url.to_string(),
+			etag: header_str(headers, ETAG.as_str()),
+			last_modified: header_str(headers, LAST_MODIFIED.as_str()),
+			total_length: resp.content_length()?,
+		};
+		// without validator there is no way to know if the file changed between attempts.
+		state.validator()?;
+		Some(state)
+	}
+
+	/// Value for `If-Range` header. Prefers ETag over Last-Modified.
+	pub fn validator(&self) -> Option<&str> {
+		self.etag.as_deref().or(self.last_modified.as_deref())
+	}
+
+	pub fn sidecar_path(temp_file: &Path) -> PathBuf {
+		let mut name = temp_file.file_name().unwrap_or_default().to_owned();
+		name.push(".state");
+		temp_file.with_file_name(name)
+	}
+
+	/// Loads state of a resumable temp file for the url. Returns the state with the amount of
+	/// bytes already downloaded.
+	///
+	/// Partial files that cannot be resumed (missing or mismatched state, or longer than the
+	/// expected length) are removed.
+	pub async fn load(temp_file: &Path, url: &str) -> Option<(Self, u64)> {
+		let sidecar = PartialDownload::sidecar_path(temp_file);
+		let state = match fs::read(&sidecar).await {
+			Ok(content) => serde_json::from_slice::<PartialDownload>(&content).ok(),
+			Err(_) => None,
+		};
+		let downloaded = fs::metadata(temp_file).await.map(|m| m.len()).unwrap_or(0);
+		match state {
+			Some(state)
+				if state.url == url && downloaded > 0 && downloaded < state.total_length =>
+			{
+				Some((state, downloaded))
+			}
+			_ => {
+				PartialDownload::discard(temp_file).await;
+				None
+			}
+		}
+	}
+
+	pub async fn save(&self, temp_file: &Path) -> std::io::Result<()> {
+		let content = serde_json::to_vec(self)?;
+		fs::write(PartialDownload::sidecar_path(temp_file), content).await
+	}
+
+	/// Removes the sidecar state, keeping the temp file.
+	pub async fn remove_state(temp_file: &Path) {
+		fs::remove_file(PartialDownload::sidecar_path(temp_file))
+			.await
+			.ok();
+	}
+
+	/// Removes both the partial temp file and its state.
+	pub async fn discard(temp_file: &Path) {
+		fs::remove_file(temp_file).await.ok();
+		PartialDownload::remove_state(temp_file).await;
+	}
+}
+
+/// Reads start offset from `Content-Range: bytes <start>-<end>/<total>` header.
+pub fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+	let value = header_str(headers, CONTENT_RANGE.as_str())?;
+	let range = value.strip_prefix("bytes ")?;
+	range.split('-').next()?.trim().parse().ok()
+}
+
+/// Reads total length from `Content-Range: bytes <start>-<end>/<total>` header.
+pub fn content_range_total(headers: &HeaderMap) -> Option<u64> {
+	let value = header_str(headers, CONTENT_RANGE.as_str())?;
+	value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+fn header_str(headers: &HeaderMap, name: &str) -> Option<String> {
+	Some(headers.get(name)?.to_str().ok()?.to_string())
+}
@@ -1,23 +1,54 @@
-use std::{convert::TryInto, path::PathBuf, sync::Arc, time::Duration, usize};
+use std::{
+	collections::HashSet,
+	convert::TryInto,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+	time::Duration,
+	usize,
+};
 
-use anyhow::{bail, Context, Error, Result};
-use imagesize::blob_size;
+use anyhow::{anyhow, bail, Context, Error, Result};
+use imagesize::{blob_size, image_type, ImageType};
+use sha2::{Digest, Sha256};
 
-use reqwest::{header::RANGE, Client, Response};
+use reqwest::{
+	header::{CONTENT_TYPE, IF_RANGE, RANGE},
+	Client, Response, StatusCode,
+};
 use tokio::{
-	fs::{self, File},
-	io::AsyncWriteExt,
+	fs::{self, File, OpenOptions},
+	io::{AsyncReadExt, AsyncWriteExt},
 	sync::{mpsc::UnboundedSender, Semaphore},
-};
-use tokio_retry::{
-	strategy::{jitter, FixedInterval},
-	Retry,
+	task,
 };
 
-use super::models::{download_meta::DownloadMeta, download_status::DownloadStatus};
+use super::{
+	auth::RedditAuth,
+	governor::{send_with_retry, RateGovernor},
+	models::{
+		download_meta::DownloadMeta,
+		download_status::DownloadStatus,
+		partial_download::{content_range_start, content_range_total, PartialDownload},
+	},
+	source::RedditSource,
+};
 use crate::api::{
-	config::{config::Config, configuration::Subreddit},
-	reddit::models::{error::RedditError, listing::Listing},
+	config::{
+		config::Config,
+		configuration::{DuplicateStrategy, StorageStrategy},
+		source::SourceConfig,
+		transform::Transform,
+	},
+	history::{
+		color::{analyze_file, ColorInfo},
+		database::History,
+		hash::{dhash_file, sha256_file, ImageHash},
+		integrity::check_image,
+	},
+	metadata::PostMetadata,
+	reddit::models::listing::Listing,
+	source::{folder::FolderSource, Source},
+	transform::{transform_file, ORIGINALS_DIR},
 };
 
 #[derive(Clone, Debug)]
@@ -25,6 +56,13 @@ pub struct Repository {
 	client: Arc<Client>,
 	config: Arc<Config>,
 	semaphore: Arc<Semaphore>,
+	history: History,
+	governor: RateGovernor,
+	auth: RedditAuth,
+	sources: Vec<Arc<dyn Source>>,
+	/// Urls already picked for download in this run. Sources run concurrently, so the same image
+	/// from several subreddits is not caught by download history yet.
+	claimed: Arc<Mutex<HashSet<String>>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -43,22 +81,68 @@ static APP_USER_AGENT: &str = concat!(
 );
 
 impl Repository {
-	pub fn new(config: Arc<Config>) -> Self {
+	pub fn new(config: Arc<Config>) -> Result<Self> {
 		let os = std::env::consts::OS;
 		let user_agent = os.to_string() + ":" + APP_USER_AGENT;
 		let client = reqwest::Client::builder()
 			.user_agent(user_agent)
 			.connect_timeout(Duration::from_secs(config.timeout.into()))
 			.build()
-			.context("failed to create request client")
-			.unwrap();
+			.context("failed to create request client")?;
 
 		let semaphore = Arc::new(Semaphore::new(config.download_threads));
-		Self {
-			client: Arc::new(client),
+		let history = History::open_default().context("failed to open download history")?;
+		let client = Arc::new(client);
+		let governor = RateGovernor::default();
+		let auth = RedditAuth::new(client.clone(), config.reddit.clone());
+		let sources = Repository::build_sources(
+			client.clone(),
+			config.clone(),
+			history.clone(),
+			auth.clone(),
+			governor.clone(),
+		);
+		Ok(Self {
+			client,
 			config,
 			semaphore,
+			history,
+			governor,
+			auth,
+			sources,
+			claimed: Arc::new(Mutex::new(HashSet::new())),
+		})
+	}
+
+	/// Creates sources for every subreddit and every registered source in configuration.
+	fn build_sources(
+		client: Arc<Client>,
+		config: Arc<Config>,
+		history: History,
+		auth: RedditAuth,
+		governor: RateGovernor,
+	) -> Vec<Arc<dyn Source>> {
+		let mut sources: Vec<Arc<dyn Source>> = Vec::new();
+		for subreddit in config.subreddits.values() {
+			sources.push(Arc::new(RedditSource::new(
+				subreddit.clone(),
+				client.clone(),
+				config.clone(),
+				history.clone(),
+				auth.clone(),
+				governor.clone(),
+			)));
+		}
+		for (name, source) in config.sources.iter() {
+			match source {
+				SourceConfig::Folder { path } => sources.push(Arc::new(FolderSource::new(
+					name.to_owned(),
+					path.to_owned(),
+					config.clone(),
+				))),
+			}
 		}
+		sources
 	}
 
 	pub async fn download(
@@ -68,12 +152,12 @@ impl Repository {
 	) -> Vec<(DownloadMeta, Result<(), Error>)> {
 		let mut handlers = Vec::new();
 
-		for (_, subreddit) in &self.config.subreddits {
+		for source in &self.sources {
 			let this = self.clone();
-			let subreddit = subreddit.clone();
+			let source = source.clone();
 			let progress = progress.clone();
 			let handle = tokio::spawn(async move {
-				Ok::<_, Error>(this.exec_download(subreddit, display, progress).await?)
+				Ok::<_, Error>(this.exec_download(source, display, progress).await?)
 			});
 			handlers.push(handle);
 		}
@@ -89,42 +173,58 @@ impl Repository {
 
 	async fn exec_download(
 		&self,
-		subreddit: Subreddit,
+		source: Arc<dyn Source>,
 		display: PrintOut,
 		progress: UnboundedSender<DownloadStatus>,
 	) -> Result<Vec<(DownloadMeta, Result<(), Error>)>> {
 		let print = || {
-			println!("{} downloading listing", subreddit.padded_proper_name());
+			println!("{} downloading listing", source.padded_name());
 		};
 		match display {
 			PrintOut::None => {}
 			_ => print(),
 		}
-		let downloads = self.download_listing(&subreddit).await?;
-		Ok(self.download_images(downloads, subreddit, progress).await)
+		let downloads = source.fetch().await?;
+		Ok(self
+			.download_images(downloads, source.download_first(), progress)
+			.await)
 	}
 
 	async fn download_images(
 		&self,
 		downloads: Vec<DownloadMeta>,
-		subreddit: Subreddit,
+		download_first: bool,
 		progress: UnboundedSender<DownloadStatus>,
 	) -> Vec<(DownloadMeta, Result<(), Error>)> {
 		let mut handlers = Vec::new();
 		'meta: for mut meta in downloads.into_iter() {
+			if let Some(reason) = meta.rejected.take() {
+				progress
+					.send(meta.as_download_status(0, 0).with_rejection(reason))
+					.unwrap();
+				continue;
+			}
+			// a failed lookup is not fatal, the file check below still prevents overwrites.
+			if self.history.is_downloaded(&meta.url).unwrap_or(false) {
+				continue;
+			}
 			for profile in &meta.profile {
-				if self.file_exists(profile, &meta).await {
+				if meta.is_stored(&self.config, profile).await {
 					continue 'meta;
 				}
 			}
+			if !self.claimed.lock().unwrap().insert(meta.url.to_owned()) {
+				continue;
+			}
 			let this = self.clone();
 			let sem = self.semaphore.clone();
-			let subreddit = subreddit.clone();
 			let progress = progress.clone();
 			let handle = tokio::spawn(async move {
 				// release semaphore lock on end of scope
 				let _x = sem.acquire().await.unwrap();
-				let op = this.download_image(&mut meta, subreddit, progress).await;
+				let op = this
+					.download_image(&mut meta, download_first, progress)
+					.await;
 				(meta, op)
 			});
 			handlers.push(handle);
@@ -136,84 +236,207 @@ impl Repository {
 		v
 	}
 
-	async fn download_listing(&self, subreddit: &Subreddit) -> Result<Vec<DownloadMeta>> {
-		let listing_url = format!(
-			"https://reddit.com/r/{}/{}.json?limit=100",
-			subreddit.proper_name, subreddit.sort
-		);
-
-		let retry_strategy = FixedInterval::from_millis(100).map(jitter).take(3);
-		let resp: Response = Retry::spawn(retry_strategy, || async {
-			let res = self.client.get(&listing_url).send().await?;
-			Ok::<Response, Error>(res)
-		})
-		.await
-		.with_context(|| {
-			format!(
-				"failed to open connection to download listing from: {}",
-				listing_url
-			)
-		})?;
-
-		if !resp.status().is_success() {
-			let err = resp.json::<RedditError>().await.with_context(|| {
-				format!("failed to deserialize json body from: {}", listing_url)
-			})?;
-			bail!(
-				"downloading listing from [{}] give error: {}",
-				subreddit.proper_name,
-				err
-			);
-		}
-
-		let listing: Listing = resp
-			.json()
-			.await
-			.with_context(|| format!("failed to deserialize json body from: {}", listing_url))?;
-
-		Ok(listing.into_download_metas(&self.config))
-	}
-
 	async fn download_image(
 		&self,
 		meta: &mut DownloadMeta,
-		subreddit: Subreddit,
+		download_first: bool,
 		progress: UnboundedSender<DownloadStatus>,
 	) -> Result<()> {
-		if subreddit.download_first {
+		if download_first {
 			self.poke_image_size(meta).await?;
 			let mut should_continue = false;
+			let mut passed = false;
+			let subreddit = self
+				.config
+				.subreddits
+				.get(&meta.subreddit_name.to_lowercase());
 			for (profile, setting) in self.config.iter() {
+				if let Some(subreddit) = subreddit {
+					if !subreddit.accepts_profile(profile) {
+						continue;
+					}
+				}
 				if !meta.passed_checks(setting) {
 					continue;
 				}
-				if self.file_exists(profile, meta).await {
+				passed = true;
+				if meta.is_stored(&self.config, profile).await {
 					continue;
 				}
 				should_continue = true;
 				meta.profile.push(profile.to_owned());
 			}
+			if !passed {
+				progress
+					.send(meta.as_download_status(0, 0).with_rejection(format!(
+						"{}x{} image of {} bytes does not fit any profile",
+						meta.image_width,
+						meta.image_height,
+						meta.file_size.unwrap_or_default()
+					)))
+					.unwrap();
+			}
 			if !should_continue {
 				return Ok(());
 			}
 		}
 
-		// println!(
-		// 	"{:?} [{}] downloading image {}",
-		// 	meta.profile, meta.subreddit_name, meta.url
-		// );
-		let retry_strategy = FixedInterval::from_millis(100).map(jitter).take(3);
-		let response: Response = Retry::spawn(retry_strategy, || async {
-			let res = self.client.get(&meta.url).send().await?;
-			Ok::<Response, Error>(res)
-		})
-		.await
-		.with_context(|| {
-			format!(
-				"failed to open connection to download image from: {}",
-				meta.url
-			)
-		})?;
+		// a failed lookup is not fatal, the colors are checked again after download.
+		if let Some(colors) = self.history.find_colors(&meta.url).unwrap_or_default() {
+			if !self.retain_by_colors(meta, Some(&colors), &progress) {
+				return Ok(());
+			}
+		}
+
+		let (temp_file, sha256) = match meta.url.strip_prefix("file://") {
+			Some(path) => {
+				self.import_to_temp(PathBuf::from(path), meta, progress.clone())
+					.await?
+			}
+			None => match self.fetch_to_temp(meta, progress.clone()).await? {
+				Some(v) => v,
+				None => return Ok(()),
+			},
+		};
+
+		let result = self.store_image(meta, &temp_file, &sha256, &progress).await;
+		// the image is downloaded again on the next run when storing fails, the partial download
+		// state is of no use anymore.
+		fs::remove_file(&temp_file).await.ok();
+		result
+	}
+
+	/// Checks the downloaded image in temp dir and stores it into every profile that accepts it.
+	async fn store_image(
+		&self,
+		meta: &mut DownloadMeta,
+		temp_file: &Path,
+		sha256: &str,
+		progress: &UnboundedSender<DownloadStatus>,
+	) -> Result<()> {
+		let file_size = fs::metadata(temp_file).await?.len();
+		if !self.retain_by_file_size(meta, file_size, progress) {
+			return Ok(());
+		}
+
+		// image checks below decode the whole image, they run in blocking threads to keep the
+		// runtime responsive.
+		let path = temp_file.to_path_buf();
+		if let Err(err) = task::spawn_blocking(move || check_image(path)).await? {
+			bail!("downloaded image from {} is corrupt: {}", meta.url, err);
+		}
+
+		// content type can lie, the actual format is checked from the file magic bytes.
+		let format = self.sniff_format(temp_file).await?;
+		if !self.retain_accepting_profiles(meta, format) {
+			return Ok(());
+		}
+		meta.set_format(format);
+
+		// a failed lookup is not fatal, worst case is a duplicate file stored.
+		let duplicates = self.history.find_by_sha256(sha256).unwrap_or_default();
+
+		let path = temp_file.to_path_buf();
+		let colors = task::spawn_blocking(move || analyze_file(path)).await?.ok();
+		if let Some(colors) = &colors {
+			// recorded before the check, so images rejected by colors are not downloaded again.
+			self.history.record_colors(&meta.url, colors)?;
+		}
+		if !self.retain_by_colors(meta, colors.as_ref(), progress) {
+			return Ok(());
+		}
+
+		let (image_hash, replaced) = self
+			.filter_near_duplicates(meta, temp_file, format, progress)
+			.await?;
+
+		let mut post_metadata = PostMetadata::from(&*meta);
+		post_metadata.colors = colors.clone();
+		self.embed_metadata(&post_metadata, temp_file, format).await;
+
+		let mut file_paths: Vec<PathBuf> = Vec::new();
+		// first untransformed copy, the other profiles refer to it.
+		let mut original: Option<PathBuf> = None;
+		for profile in &meta.profile {
+			let duplicate = match (
+				self.config.duplicate,
+				self.duplicate_in_profile(profile, &duplicates).await,
+			) {
+				(DuplicateStrategy::Skip, Some(_)) => continue,
+				(DuplicateStrategy::Hardlink, Some(existing)) => Some(existing),
+				_ => None,
+			};
+			let transform = self
+				.config
+				.get(profile)
+				.map(|setting| setting.transform)
+				.filter(|transform| transform.enable);
+			let transformed = match &transform {
+				Some(transform) => {
+					self.transform_image(profile, temp_file, format, transform, &post_metadata)
+						.await?
+				}
+				None => None,
+			};
+			let mut download_location = meta.file_location(&self.config, profile);
+			if let (Some(transform), Some(_)) = (&transform, &transformed) {
+				download_location.set_extension(transform.format.extension(format));
+			}
+			let download_location = self.unique_location(download_location).await;
+			let stored = match (duplicate, &transformed) {
+				(Some(existing), _)
+					if fs::hard_link(&existing, &download_location).await.is_ok() =>
+				{
+					Ok(())
+				}
+				(_, Some(transformed)) => self.store_file(transformed, &download_location).await,
+				(_, None) => {
+					self.store_original(&mut original, temp_file, &download_location)
+						.await
+				}
+			};
+			if let Some(transformed) = &transformed {
+				fs::remove_file(transformed).await.ok();
+			}
+			stored?;
+			let keep_original = transform.map(|t| t.keep_original).unwrap_or(false);
+			if transformed.is_some() && keep_original {
+				let location = self.original_location(profile, meta).await?;
+				self.store_original(&mut original, temp_file, &location)
+					.await?;
+			}
+			if self.config.metadata.sidecar {
+				post_metadata.write_sidecar(&download_location).await?;
+			}
+			if let Some(image_hash) = &image_hash {
+				self.history
+					.record_perceptual_hash(profile, &download_location, image_hash)?;
+			}
+			file_paths.push(download_location);
+		}
+
+		self.history.record(meta, &file_paths, sha256)?;
+		self.remove_replaced(&replaced).await?;
+
+		Ok(())
+	}
+
+	/// Downloads the image into temp dir. Returns `None` if no profile accepts the image content
+	/// type or size. Partial download of a rejected image is removed.
+	async fn fetch_to_temp(
+		&self,
+		meta: &mut DownloadMeta,
+		progress: UnboundedSender<DownloadStatus>,
+	) -> Result<Option<(PathBuf, String)>> {
+		let temp_file = self.temp_location(meta).await?;
+		let mut partial = PartialDownload::load(&temp_file, &meta.url).await;
+
+		let mut response = self.request_image(meta, partial.as_ref()).await?;
+		if partial.is_some() && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+			PartialDownload::discard(&temp_file).await;
+			partial = None;
+			response = self.request_image(meta, None).await?;
+		}
 
 		let status = response.status();
 		if !status.is_success() {
@@ -225,38 +448,249 @@ impl Repository {
 			));
 		}
 
+		// CDNs may send generic content type, e.g. `application/octet-stream`. Those are left for
+		// the file magic bytes check after download.
+		let format = response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|v| v.to_str().ok())
+			.and_then(|content_type| {
+				content_type
+					.split(';')
+					.next()
+					.unwrap()
+					.strip_prefix("image/")
+			})
+			.map(str::trim);
+		if let Some(format) = format {
+			if !self.retain_accepting_profiles(meta, format) {
+				PartialDownload::discard(&temp_file).await;
+				return Ok(None);
+			}
+		}
+
+		if let Some(length) = response.content_length() {
+			let total = match &partial {
+				Some((_, offset)) if status == StatusCode::PARTIAL_CONTENT => offset + length,
+				_ => length,
+			};
+			if !self.retain_by_file_size(meta, total, &progress) {
+				PartialDownload::discard(&temp_file).await;
+				return Ok(None);
+			}
+		}
+
 		self.ensure_download_dir(meta).await?;
 
-		let temp_file = self.store_to_temp(response, meta, progress).await?;
+		// server may send the whole file instead when the file has changed since last attempt.
+		let offset = match partial {
+			Some((_, offset)) if status == StatusCode::PARTIAL_CONTENT => {
+				if content_range_start(response.headers()) != Some(offset) {
+					PartialDownload::discard(&temp_file).await;
+					bail!(
+						"download from {} gives unexpected content range, partial file discarded",
+						meta.url
+					);
+				}
+				offset
+			}
+			_ => {
+				match PartialDownload::from_response(&meta.url, &response) {
+					Some(state) => state.save(&temp_file).await.with_context(|| {
+						format!("failed to save download state of {}", temp_file.display())
+					})?,
+					None => PartialDownload::remove_state(&temp_file).await,
+				}
+				0
+			}
+		};
 
-		for profile in &meta.profile {
-			let download_location = self.download_location(profile, meta);
-			fs::copy(&temp_file, &download_location)
+		let result = self.store_to_temp(response, meta, progress, offset).await?;
+		PartialDownload::remove_state(&temp_file).await;
+		Ok(Some(result))
+	}
+
+	/// Requests the image. Resumes from the partial download if given.
+	async fn request_image(
+		&self,
+		meta: &DownloadMeta,
+		partial: Option<&(PartialDownload, u64)>,
+	) -> Result<Response> {
+		let mut request = self.client.get(&meta.url);
+		if let Some((state, offset)) = partial {
+			request = request.header(RANGE, format!("bytes={}-", offset));
+			if let Some(validator) = state.validator() {
+				request = request.header(IF_RANGE, validator);
+			}
+		}
+		send_with_retry(request).await.with_context(|| {
+			format!(
+				"failed to open connection to download image from: {}",
+				meta.url
+			)
+		})
+	}
+
+	/// Copies a local image into temp dir.
+	async fn import_to_temp(
+		&self,
+		path: PathBuf,
+		meta: &DownloadMeta,
+		progress: UnboundedSender<DownloadStatus>,
+	) -> Result<(PathBuf, String)> {
+		self.ensure_download_dir(meta).await?;
+		let file_path = self.temp_location(meta).await?;
+		let length = fs::copy(&path, &file_path)
+			.await
+			.with_context(|| format!("failed to copy file from {} to tmp dir", path.display()))?;
+		progress
+			.send(meta.as_download_status(length, length).set_finished())
+			.unwrap();
+		let sha256 = sha256_file(&file_path).await?;
+		Ok((file_path, sha256))
+	}
+
+	/// Creates temp download dir for the meta and returns the temp file location.
+	async fn temp_location(&self, meta: &DownloadMeta) -> Result<PathBuf> {
+		let dir_path = std::env::temp_dir()
+			.join("ridit")
+			.join(&meta.subreddit_name);
+		fs::create_dir_all(&dir_path).await?;
+		Ok(dir_path.join(&meta.filename))
+	}
+
+	/// Copies the file into `{to}.part` first, then renames it to the destination, so an
+	/// interrupted copy never leaves a half written image in the download directory.
+	async fn store_file(&self, from: &Path, to: &Path) -> Result<()> {
+		let mut part = to.as_os_str().to_owned();
+		part.push(".part");
+		let part = PathBuf::from(part);
+		let result = async {
+			fs::copy(from, &part).await.with_context(|| {
+				format!("failed to copy file from tmp dir to {}", part.display())
+			})?;
+			fs::rename(&part, to)
 				.await
-				.with_context(|| {
-					format!(
-						"failed to copy file from tmp dir to {}",
-						download_location.display()
-					)
-				})?;
-			let dir_path = std::env::temp_dir()
-				.join("ridit")
-				.join(&meta.subreddit_name)
-				.join(&meta.filename);
-			fs::remove_file(&dir_path).await.with_context(|| {
+				.with_context(|| format!("failed to rename {} to {}", part.display(), to.display()))
+		}
+		.await;
+		if result.is_err() {
+			fs::remove_file(&part).await.ok();
+		}
+		result
+	}
+
+	/// Stores the image referring to an already stored copy, using the configured storage
+	/// strategy. Falls back to copy from temp file when the strategy fails.
+	async fn link_file(&self, stored: &Path, temp_file: &Path, to: &Path) -> Result<()> {
+		let linked = match self.config.storage {
+			StorageStrategy::Copy => false,
+			StorageStrategy::Hardlink => fs::hard_link(stored, to).await.is_ok(),
+			StorageStrategy::Symlink => symlink(stored, to).await.is_ok(),
+			StorageStrategy::Reflink => {
+				let (stored, to) = (stored.to_path_buf(), to.to_path_buf());
+				task::spawn_blocking(move || reflink::reflink(stored, to))
+					.await?
+					.is_ok()
+			}
+		};
+		if linked {
+			return Ok(());
+		}
+		self.store_file(temp_file, to).await
+	}
+
+	/// Stores the downloaded image as is. The first stored copy is kept in `original` and later
+	/// copies refer to it following the storage strategy.
+	async fn store_original(
+		&self,
+		original: &mut Option<PathBuf>,
+		temp_file: &Path,
+		to: &Path,
+	) -> Result<()> {
+		match original {
+			Some(stored) => self.link_file(stored, temp_file, to).await,
+			None => {
+				self.store_file(temp_file, to).await?;
+				*original = Some(to.to_path_buf());
+				Ok(())
+			}
+		}
+	}
+
+	/// Crops, resizes and re-encodes the downloaded image for the profile into a new file next to
+	/// the temp file. Returns `None` when the image cannot be transformed, e.g. formats the image
+	/// decoder or encoder does not support, so the image is stored as is.
+	async fn transform_image(
+		&self,
+		profile: &str,
+		temp_file: &Path,
+		format: &str,
+		transform: &Transform,
+		post_metadata: &PostMetadata,
+	) -> Result<Option<PathBuf>> {
+		let ratio = self
+			.config
+			.get(profile)
+			.and_then(|setting| setting.crop_ratio());
+		let (path, source_format, settings) =
+			(temp_file.to_path_buf(), format.to_string(), *transform);
+		let data = match task::spawn_blocking(move || {
+			transform_file(path, &source_format, &settings, ratio)
+		})
+		.await?
+		{
+			Ok(data) => data,
+			Err(_) => return Ok(None),
+		};
+
+		let mut transformed = temp_file.as_os_str().to_owned();
+		transformed.push(format!(".{}", profile));
+		let transformed = PathBuf::from(transformed);
+		if let Err(err) = fs::write(&transformed, data).await {
+			fs::remove_file(&transformed).await.ok();
+			return Err(err).with_context(|| {
 				format!(
-					"failed to remove temp downloaded file {}",
-					download_location.display()
+					"failed to write transformed image {}",
+					transformed.display()
 				)
-			})?;
+			});
 		}
+		let format = transform.format.extension(format);
+		self.embed_metadata(post_metadata, &transformed, format)
+			.await;
+		Ok(Some(transformed))
+	}
 
-		Ok(())
+	/// Embeds post metadata into the image if enabled. Failing is not fatal, the image is still
+	/// stored as is.
+	async fn embed_metadata(&self, post_metadata: &PostMetadata, path: &Path, format: &str) {
+		if self.config.metadata.embed {
+			post_metadata.embed(path, format).await.ok();
+		}
+	}
+
+	/// Location to keep the untouched image of a transforming profile. Creates the parent
+	/// directory.
+	async fn original_location(&self, profile: &str, meta: &DownloadMeta) -> Result<PathBuf> {
+		let template = self.config.filename_template(profile);
+		let location = self
+			.config
+			.path
+			.join(profile)
+			.join(ORIGINALS_DIR)
+			.join(meta.render_filename(template));
+		let dir = location.parent().unwrap_or(&self.config.path);
+		fs::create_dir_all(dir).await.with_context(|| {
+			format!("failed to create originals directory on: {}", dir.display())
+		})?;
+		Ok(self.unique_location(location).await)
 	}
 
 	async fn ensure_download_dir(&self, meta: &DownloadMeta) -> Result<()> {
 		for profile in &meta.profile {
-			let download_dir = self.config.path.join(profile).join(&meta.subreddit_name);
+			let location = meta.file_location(&self.config, profile);
+			let download_dir = location.parent().unwrap_or(&self.config.path);
 			fs::create_dir_all(&download_dir).await.with_context(|| {
 				format!(
 					"failed to create download directory on: {}",
@@ -271,16 +705,11 @@ impl Repository {
 	/// DownloadMeta information on success. Note this does not download the whole file.
 	async fn poke_image_size(&self, meta: &mut DownloadMeta) -> Result<()> {
 		const LIMIT: usize = 1024 * 2 * 10;
-		let retry_strategy = FixedInterval::from_millis(100).map(jitter).take(3);
-		let mut resp = Retry::spawn(retry_strategy, || async {
-			let res = self
-				.client
+		let mut resp = send_with_retry(
+			self.client
 				.get(&meta.url)
-				.header(RANGE, LIMIT)
-				.send()
-				.await?;
-			Ok::<Response, Error>(res)
-		})
+				.header(RANGE, format!("bytes=0-{}", LIMIT - 1)),
+		)
 		.await
 		.with_context(|| {
 			format!(
@@ -288,6 +717,10 @@ impl Repository {
 				meta.url
 			)
 		})?;
+		meta.file_size = match resp.status() {
+			StatusCode::PARTIAL_CONTENT => content_range_total(resp.headers()),
+			_ => resp.content_length(),
+		};
 		let mut data: Vec<u8> = Vec::new();
 		while let Some(chunk) = resp.chunk().await? {
 			data.append(&mut chunk.to_vec());
@@ -310,18 +743,222 @@ impl Repository {
 		Ok(())
 	}
 
-	fn download_dir(&self, profile: &str, meta: &DownloadMeta) -> PathBuf {
-		self.config.path.join(profile).join(&meta.subreddit_name)
+	/// Appends `_2`, `_3`, ... to the file name until the location does not point to an existing
+	/// file.
+	async fn unique_location(&self, location: PathBuf) -> PathBuf {
+		if fs::symlink_metadata(&location).await.is_err() {
+			return location;
+		}
+		let stem = location
+			.file_stem()
+			.unwrap_or_default()
+			.to_string_lossy()
+			.to_string();
+		let ext = location
+			.extension()
+			.map(|ext| format!(".{}", ext.to_string_lossy()))
+			.unwrap_or_default();
+		let mut n = 2;
+		loop {
+			let candidate = location.with_file_name(format!("{}_{}{}", stem, n, ext));
+			if fs::symlink_metadata(&candidate).await.is_err() {
+				return candidate;
+			}
+			n += 1;
+		}
+	}
+
+	/// Removes profiles from meta that do not accept the image format.
+	/// Returns false if there is no profile left.
+	fn retain_accepting_profiles(&self, meta: &mut DownloadMeta, format: &str) -> bool {
+		meta.profile.retain(|profile| {
+			self.config
+				.get(profile)
+				.map(|setting| setting.accepts_format(format))
+				.unwrap_or(false)
+		});
+		!meta.profile.is_empty()
+	}
+
+	/// Removes profiles from meta that do not accept the file size.
+	/// Returns false and reports the rejection if there is no profile left.
+	fn retain_by_file_size(
+		&self,
+		meta: &mut DownloadMeta,
+		size: u64,
+		progress: &UnboundedSender<DownloadStatus>,
+	) -> bool {
+		meta.file_size = Some(size);
+		let rejected = meta.as_download_status(0, 0);
+		meta.profile.retain(|profile| {
+			self.config
+				.get(profile)
+				.map(|setting| setting.file_size.accepts(size))
+				.unwrap_or(false)
+		});
+		if meta.profile.is_empty() {
+			progress
+				.send(
+					rejected
+						.with_rejection(format!("file size of {} bytes is out of bounds", size)),
+				)
+				.unwrap();
+			return false;
+		}
+		true
+	}
+
+	/// Removes profiles from meta that reject the image colors, `None` if the image cannot be
+	/// decoded. Returns false and reports the rejection if there is no profile left.
+	fn retain_by_colors(
+		&self,
+		meta: &mut DownloadMeta,
+		colors: Option<&ColorInfo>,
+		progress: &UnboundedSender<DownloadStatus>,
+	) -> bool {
+		let rejected = meta.as_download_status(0, 0);
+		let mut reasons = Vec::new();
+		meta.profile.retain(|profile| {
+			match self
+				.config
+				.get(profile)
+				.map(|setting| setting.color.check(colors))
+			{
+				Some(Ok(_)) => true,
+				Some(Err(reason)) => {
+					reasons.push(format!("{}: {}", profile, reason));
+					false
+				}
+				None => false,
+			}
+		});
+		if meta.profile.is_empty() {
+			progress
+				.send(rejected.with_rejection(reasons.join(", ")))
+				.unwrap();
+			return false;
+		}
+		true
 	}
 
-	fn download_location(&self, profile: &str, meta: &DownloadMeta) -> PathBuf {
-		self.download_dir(profile, meta).join(&meta.filename)
+	/// Removes near duplicates replaced by a newly stored image. Other profiles linking to a
+	/// removed file with a symbolic link get a copy of it instead.
+	async fn remove_replaced(&self, replaced: &[PathBuf]) -> Result<()> {
+		for path in replaced {
+			if let Ok(target) = fs::canonicalize(path).await {
+				for sibling in self.history.sibling_files(path)? {
+					match fs::read_link(&sibling).await {
+						Ok(link) if link == target => self.store_file(path, &sibling).await?,
+						_ => {}
+					}
+				}
+			}
+			fs::remove_file(path).await.ok();
+			self.history.remove_perceptual_hash(path)?;
+			self.history.remove_file_path(path)?;
+		}
+		Ok(())
 	}
 
-	async fn file_exists(&self, profile: &str, meta: &DownloadMeta) -> bool {
-		fs::metadata(self.download_location(profile, meta))
+	/// Detects image format from the file header.
+	async fn sniff_format(&self, path: &Path) -> Result<&'static str> {
+		let mut header = [0; 1024];
+		let mut file = File::open(path)
 			.await
-			.is_ok()
+			.with_context(|| format!("failed to open file {}", path.display()))?;
+		let n = file.read(&mut header).await?;
+		let image_type = image_type(&header[..n]).map_err(|_| {
+			anyhow!(
+				"downloaded file {} is not a recognized image",
+				path.display()
+			)
+		})?;
+		Ok(match image_type {
+			ImageType::Bmp => "bmp",
+			ImageType::Gif => "gif",
+			ImageType::Heif if is_avif(&header[..n]) => "avif",
+			ImageType::Heif => bail!(
+				"downloaded file {} is a heif image, only avif is supported",
+				path.display()
+			),
+			ImageType::Jpeg => "jpg",
+			ImageType::Jxl => "jxl",
+			ImageType::Png => "png",
+			ImageType::Psd => "psd",
+			ImageType::Tiff => "tiff",
+			ImageType::Webp => "webp",
+		})
+	}
+
+	/// Removes profiles from meta that already store a similar looking image with the same or higher
+	/// resolution.
+	///
+	/// Returns perceptual hash of the image if any of the profiles has near duplicate check enabled,
+	/// and the similar images with lower resolution to be replaced once the image is stored.
+	/// Images that cannot be decoded are not checked, which is reported as warning.
+	async fn filter_near_duplicates(
+		&self,
+		meta: &mut DownloadMeta,
+		temp_file: &Path,
+		format: &str,
+		progress: &UnboundedSender<DownloadStatus>,
+	) -> Result<(Option<ImageHash>, Vec<PathBuf>)> {
+		let enabled = meta.profile.iter().any(|profile| {
+			self.config
+				.get(profile)
+				.map(|setting| setting.near_duplicate.enable)
+				.unwrap_or(false)
+		});
+		if !enabled {
+			return Ok((None, Vec::new()));
+		}
+		let path = temp_file.to_path_buf();
+		let image = match task::spawn_blocking(move || dhash_file(path)).await? {
+			Ok(image) => image,
+			Err(_) => {
+				progress
+					.send(meta.as_download_status(0, 0).with_warning(format!(
+						"near duplicate check is skipped, {} image cannot be decoded",
+						format
+					)))
+					.unwrap();
+				return Ok((None, Vec::new()));
+			}
+		};
+		let pixels = |img: &ImageHash| img.width as u64 * img.height as u64;
+
+		let mut rejected = Vec::new();
+		let mut replaced = Vec::new();
+		for profile in &meta.profile {
+			let setting = match self.config.get(profile) {
+				Some(setting) if setting.near_duplicate.enable => setting,
+				_ => continue,
+			};
+			let similar =
+				self.history
+					.find_similar(profile, &image, setting.near_duplicate.distance)?;
+			if similar
+				.iter()
+				.any(|(_, existing)| pixels(existing) >= pixels(&image))
+			{
+				rejected.push(profile.to_owned());
+				continue;
+			}
+			replaced.extend(similar.into_iter().map(|(path, _)| path));
+		}
+		meta.profile.retain(|profile| !rejected.contains(profile));
+		Ok((Some(image), replaced))
+	}
+
+	/// Finds a file among the given paths that is stored under the profile directory and still exists.
+	async fn duplicate_in_profile(&self, profile: &str, paths: &[PathBuf]) -> Option<PathBuf> {
+		let profile_dir = self.config.path.join(profile);
+		for path in paths {
+			if path.starts_with(&profile_dir) && fs::metadata(path).await.is_ok() {
+				return Some(path.to_owned());
+			}
+		}
+		None
 	}
 
 	async fn store_to_temp(
@@ -329,26 +966,54 @@ impl Repository {
 		mut resp: Response,
 		meta: &DownloadMeta,
 		progress: UnboundedSender<DownloadStatus>,
-	) -> Result<PathBuf> {
-		let dir_path = std::env::temp_dir()
-			.join("ridit")
-			.join(&meta.subreddit_name);
-		fs::create_dir_all(&dir_path).await?;
-		let file_path = dir_path.join(&meta.filename);
-		let mut file = File::create(&file_path)
-			.await
-			.context("cannot create file on tmp dir")?;
+		offset: u64,
+	) -> Result<(PathBuf, String)> {
+		let file_path = self.temp_location(meta).await?;
+		let mut hasher = Sha256::new();
+		let mut file = if offset > 0 {
+			let existing = fs::read(&file_path)
+				.await
+				.context("cannot read partial file on tmp dir")?;
+			hasher.update(&existing);
+			OpenOptions::new()
+				.append(true)
+				.open(&file_path)
+				.await
+				.context("cannot open partial file on tmp dir")?
+		} else {
+			File::create(&file_path)
+				.await
+				.context("cannot create file on tmp dir")?
+		};
 
-		let download_length = resp.content_length().unwrap_or(0);
+		let content_length = resp.content_length();
+		let download_length = offset + content_length.unwrap_or(0);
+		let mut received = offset;
 		progress
 			.send(meta.as_download_status(download_length, 0))
 			.unwrap();
-		while let Some(chunk) = resp.chunk().await? {
+		if offset > 0 {
+			progress
+				.send(meta.as_download_status(download_length, offset))
+				.unwrap();
+		}
+		loop {
+			let chunk = match resp.chunk().await {
+				Ok(Some(chunk)) => chunk,
+				Ok(None) => break,
+				Err(err) => {
+					// keep what is received so far so the next attempt can resume from it.
+					file.flush().await.ok();
+					return Err(err).with_context(|| {
+						format!("connection interrupted while downloading {}", meta.url)
+					});
+				}
+			};
 			progress
 				.send(meta.as_download_status(download_length, chunk.len().try_into().unwrap()))
 				.unwrap();
 
-			if let Err(err) = file.write(&chunk).await {
+			if let Err(err) = file.write_all(&chunk).await {
 				progress
 					.send(
 						meta.as_download_status(download_length, chunk.len().try_into().unwrap())
@@ -357,24 +1022,52 @@ impl Repository {
 					.unwrap();
 				bail!("failed to save image from {}. cause: {}", meta.url, err)
 			}
+			hasher.update(&chunk);
+			received += chunk.len() as u64;
+		}
+		file.flush().await?;
+		if content_length.is_some() && received != download_length {
+			let err = anyhow!(
+				"download from {} is incomplete: received {} of {} bytes",
+				meta.url,
+				received,
+				download_length
+			);
+			// a shorter file can still be resumed, anything else is garbage.
+			if received > download_length {
+				PartialDownload::discard(&file_path).await;
+			}
+			progress
+				.send(
+					meta.as_download_status(download_length, 0)
+						.with_error(err.to_string()),
+				)
+				.unwrap();
+			return Err(err);
 		}
 		progress
 			.send(meta.as_download_status(download_length, 0).set_finished())
 			.unwrap();
-		Ok(file_path)
+		Ok((file_path, format!("{:x}", hasher.finalize())))
 	}
 
 	/// Checks to reddit if subreddit exists
 	/// Also mutates the given subreddit name to proper casing.
-	pub async fn subreddit_exist(subreddit: &mut String) -> Result<bool> {
-		let url = format!("https://reddit.com/r/{}.json", subreddit);
-		let retry_strategy = FixedInterval::from_millis(100).map(jitter).take(3);
-		let resp: Response = Retry::spawn(retry_strategy, || async {
-			let res = reqwest::get(&url).await?;
-			Ok::<Response, Error>(res)
-		})
-		.await
-		.with_context(|| format!("failed to check subreddit {}", subreddit))?;
+	pub async fn subreddit_exist(&self, subreddit: &mut String) -> Result<bool> {
+		let url = format!("{}/r/{}/new.json?limit=1", self.auth.api_base(), subreddit);
+		let request = self.auth.authorize(self.client.get(&url)).await?;
+		let resp: Response = self
+			.governor
+			.send(request)
+			.await
+			.with_context(|| format!("failed to check subreddit {}", subreddit))?;
+		if !resp.status().is_success() {
+			bail!(
+				"checking subreddit {} gives [{}] status code",
+				subreddit,
+				resp.status().as_u16()
+			);
+		}
 
 		let listing: Listing = resp
 			.json()
@@ -391,3 +1084,34 @@ impl Repository {
 		}
 	}
 }
+
+/// Checks the brands of the `ftyp` box of a HEIF file for AVIF, other HEIF files (e.g. HEIC) use
+/// the same container.
+fn is_avif(header: &[u8]) -> bool {
+	if header.get(4..8) != Some(b"ftyp") {
+		return false;
+	}
+	let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+	let brands = match header.get(8..size.min(header.len())) {
+		Some(brands) => brands,
+		None => return false,
+	};
+	// major brand, minor version, then compatible brands.
+	brands
+		.chunks_exact(4)
+		.enumerate()
+		.filter(|(i, _)| *i != 1)
+		.any(|(_, brand)| brand == b"avif" || brand == b"avis")
+}
+
+/// Creates symbolic link with absolute target, so the link stays valid wherever it's read from.
+#[cfg(unix)]
+async fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+	let original = fs::canonicalize(original).await?;
+	fs::symlink(original, link).await
+}
+
+#[cfg(not(unix))]
+async fn symlink(_: &Path, _: &Path) -> std::io::Result<()> {
+	Err(std::io::ErrorKind::Unsupported.into())
+}
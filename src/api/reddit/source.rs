@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Response};
+
+use super::{
+	auth::RedditAuth,
+	governor::RateGovernor,
+	link_resolver::LinkResolver,
+	models::{
+		download_meta::DownloadMeta,
+		error::RedditError,
+		listing::{ChildrenData, Listing},
+	},
+};
+use crate::api::{
+	config::{
+		config::Config,
+		configuration::{Sort, Subreddit},
+	},
+	history::database::History,
+	source::Source,
+};
+
+/// Fetches images from a subreddit listing.
+#[derive(Debug, Clone)]
+pub struct RedditSource {
+	subreddit: Subreddit,
+	client: Arc<Client>,
+	config: Arc<Config>,
+	resolver: LinkResolver,
+	history: History,
+	auth: RedditAuth,
+	governor: RateGovernor,
+}
+
+impl RedditSource {
+	pub fn new(
+		subreddit: Subreddit,
+		client: Arc<Client>,
+		config: Arc<Config>,
+		history: History,
+		auth: RedditAuth,
+		governor: RateGovernor,
+	) -> Self {
+		let resolver = LinkResolver::new(client.clone(), &config);
+		Self {
+			subreddit,
+			client,
+			config,
+			resolver,
+			history,
+			auth,
+			governor,
+		}
+	}
+
+	async fn fetch_listing(&self, after: Option<&str>) -> Result<Listing> {
+		let subreddit = &self.subreddit;
+		let mut listing_url = format!(
+			"{}/r/{}/{}.json?limit=100",
+			self.auth.api_base(),
+			subreddit.proper_name,
+			subreddit.sort
+		);
+		if subreddit.sort.is_timed() {
+			listing_url.push_str("&t=");
+			listing_url.push_str(&subreddit.time.to_string());
+		}
+		if let Some(after) = after {
+			listing_url.push_str("&after=");
+			listing_url.push_str(after);
+		}
+
+		let req = self.auth.authorize(self.client.get(&listing_url)).await?;
+		let resp: Response = self.governor.send(req).await.with_context(|| {
+			format!(
+				"failed to open connection to download listing from: {}",
+				listing_url
+			)
+		})?;
+
+		if !resp.status().is_success() {
+			let err = resp.json::<RedditError>().await.with_context(|| {
+				format!("failed to deserialize json body from: {}", listing_url)
+			})?;
+			bail!(
+				"downloading listing from [{}] give error: {}",
+				subreddit.proper_name,
+				err
+			);
+		}
+
+		let mut listing: Listing = resp
+			.json()
+			.await
+			.with_context(|| format!("failed to deserialize json body from: {}", listing_url))?;
+
+		self.resolver
+			.resolve(&mut listing, |data| self.wants_post(data))
+			.await;
+
+		Ok(listing)
+	}
+
+	/// Checks if the post passes the subreddit policy and NSFW setting, and has not been
+	/// downloaded before.
+	fn wants_post(&self, data: &ChildrenData) -> bool {
+		let sub = match self.config.subreddits.get(&data.subreddit.to_lowercase()) {
+			Some(sub) => sub,
+			None => return false,
+		};
+		sub.policy.accepts(data)
+			&& !data.is_video
+			&& (sub.nsfw || !data.over_18)
+			&& !self.history.is_post_downloaded(&data.id).unwrap_or(false)
+	}
+
+	/// Checks if any of the metas has been downloaded before.
+	async fn any_downloaded(&self, metas: &[DownloadMeta]) -> bool {
+		for meta in metas {
+			if self.history.is_downloaded(&meta.url).unwrap_or(false) {
+				return true;
+			}
+			for profile in &meta.profile {
+				if meta.is_stored(&self.config, profile).await {
+					return true;
+				}
+			}
+		}
+		false
+	}
+}
+
+#[async_trait]
+impl Source for RedditSource {
+	fn name(&self) -> &str {
+		&self.subreddit.proper_name
+	}
+
+	fn download_first(&self) -> bool {
+		self.subreddit.download_first
+	}
+
+	async fn fetch(&self) -> Result<Vec<DownloadMeta>> {
+		let mut result = Vec::new();
+		let mut after: Option<String> = None;
+		for _ in 0..self.subreddit.max_pages.max(1) {
+			let listing = self.fetch_listing(after.as_deref()).await?;
+			after = listing.data.after.clone();
+			let metas = listing.into_download_metas(&self.config);
+			// New listing is ordered by time, so everything after a seen post has been seen as well.
+			let reached_seen =
+				matches!(self.subreddit.sort, Sort::New) && self.any_downloaded(&metas).await;
+			result.extend(metas);
+			if reached_seen || after.is_none() {
+				break;
+			}
+		}
+		Ok(result)
+	}
+}
@@ -0,0 +1,89 @@
+use std::{path::PathBuf, sync::Arc, time::UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::fs;
+
+use super::Source;
+use crate::api::{config::config::Config, reddit::models::download_meta::DownloadMeta};
+
+/// Imports images from a local folder. Subdirectories are not scanned.
+#[derive(Debug, Clone)]
+pub struct FolderSource {
+	name: String,
+	path: PathBuf,
+	config: Arc<Config>,
+}
+
+impl FolderSource {
+	pub fn new(name: String, path: PathBuf, config: Arc<Config>) -> Self {
+		Self { name, path, config }
+	}
+}
+
+#[async_trait]
+impl Source for FolderSource {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	async fn fetch(&self) -> Result<Vec<DownloadMeta>> {
+		let mut result = Vec::new();
+		let mut entries = fs::read_dir(&self.path)
+			.await
+			.with_context(|| format!("failed to read directory {}", self.path.display()))?;
+		while let Some(entry) = entries.next_entry().await? {
+			if !entry.file_type().await?.is_file() {
+				continue;
+			}
+			let path = entry.path();
+			let filename = entry.file_name().to_string_lossy().to_string();
+			let (stem, ext) = match filename.rsplit_once('.') {
+				Some(v) => v,
+				None => continue,
+			};
+			if !self.config.accepts_format(ext) {
+				continue;
+			}
+			let size = match imagesize::size(&path) {
+				Ok(size) => size,
+				Err(_) => continue,
+			};
+			let metadata = entry.metadata().await?;
+			let created = metadata
+				.modified()
+				.ok()
+				.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+				.map(|d| d.as_secs() as i64)
+				.unwrap_or_default();
+			let url = format!("file://{}", path.display());
+			let mut meta = DownloadMeta {
+				post_id: stem.to_string(),
+				url: url.to_owned(),
+				subreddit_name: self.name.to_owned(),
+				image_height: size.height as u32,
+				image_width: size.width as u32,
+				post_link: url,
+				nsfw: false,
+				filename: filename.to_owned(),
+				title: stem.to_string(),
+				author: String::new(),
+				created,
+				file_size: Some(metadata.len()),
+				flair: None,
+				score: None,
+				upvote_ratio: None,
+				num_comments: None,
+				rejected: None,
+				profile: Vec::new(),
+			};
+			if meta.assign_profiles(&self.config.settings) {
+				result.push(meta);
+			} else if let Some(reason) = meta.filter_rejection(&self.config.settings) {
+				meta.rejected = Some(reason);
+				result.push(meta);
+			}
+		}
+		Ok(result)
+	}
+}
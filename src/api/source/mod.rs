@@ -0,0 +1,32 @@
+pub mod folder;
+
+use std::fmt::Debug;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use pad::PadStr;
+
+use crate::api::reddit::models::download_meta::DownloadMeta;
+
+/// A place to fetch images from, e.g. a subreddit or a local folder.
+///
+/// Every source goes through the same pipeline after fetching: profile checks, deduplication and
+/// progress reporting.
+#[async_trait]
+pub trait Source: Debug + Send + Sync {
+	/// Name of the source. Images are stored under a directory with this name in each profile.
+	fn name(&self) -> &str;
+
+	/// Returns true if images must be downloaded first before they can be checked for size.
+	/// Metas from such sources have no profile assigned yet.
+	fn download_first(&self) -> bool {
+		false
+	}
+
+	/// Lists images available from the source.
+	async fn fetch(&self) -> Result<Vec<DownloadMeta>>;
+
+	fn padded_name(&self) -> String {
+		("[".to_string() + self.name() + "]").pad_to_width(23)
+	}
+}
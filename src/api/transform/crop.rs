@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView, GrayImage};
+
+use crate::api::config::transform::Crop;
+
+/// Longest side (in pixels) of the downscaled copy used to find the detailed part of the image.
+const ANALYSIS_SIZE: u32 = 256;
+/// Maximum number of pixels (of the downscaled copy) trimmed at a time.
+const SLICE: u32 = 4;
+
+/// Crops the image to the largest area with the aspect ratio `(width, height)`.
+pub fn crop(
+	img: DynamicImage,
+	mode: Crop,
+	(ratio_width, ratio_height): (u32, u32),
+) -> DynamicImage {
+	let (width, height) = img.dimensions();
+	let (crop_width, crop_height) =
+		if width as u64 * ratio_height as u64 > height as u64 * ratio_width as u64 {
+			(
+				(height as u64 * ratio_width as u64 / ratio_height as u64) as u32,
+				height,
+			)
+		} else {
+			(
+				width,
+				(width as u64 * ratio_height as u64 / ratio_width as u64) as u32,
+			)
+		};
+	if crop_width == 0 || crop_height == 0 || (crop_width, crop_height) == (width, height) {
+		return img;
+	}
+
+	let (x, y) = match mode {
+		Crop::Off => return img,
+		Crop::Center => ((width - crop_width) / 2, (height - crop_height) / 2),
+		Crop::Smart => smart_offset(&img, crop_width, crop_height),
+	};
+	img.crop_imm(x, y, crop_width, crop_height)
+}
+
+/// Finds the crop position by repeatedly trimming the edge with less detail (lower entropy).
+fn smart_offset(img: &DynamicImage, crop_width: u32, crop_height: u32) -> (u32, u32) {
+	let (width, height) = img.dimensions();
+	let scale = (ANALYSIS_SIZE as f32 / width.max(height) as f32).min(1.0);
+	let scaled = |v: u32| ((v as f32 * scale).round() as u32).max(1);
+	let small = img
+		.resize_exact(scaled(width), scaled(height), FilterType::Triangle)
+		.to_luma8();
+	let (small_width, small_height) = small.dimensions();
+	let unscaled = |v: u32| (v as f32 / scale).round() as u32;
+
+	if crop_width < width {
+		let start = trim_offset(small_width, scaled(crop_width).min(small_width), |xs| {
+			entropy(&small, xs, 0..small_height)
+		});
+		(unscaled(start).min(width - crop_width), 0)
+	} else {
+		let start = trim_offset(small_height, scaled(crop_height).min(small_height), |ys| {
+			entropy(&small, 0..small_width, ys)
+		});
+		(0, unscaled(start).min(height - crop_height))
+	}
+}
+
+/// Trims `length` down to `target` from both ends. Returns start of the remaining range.
+fn trim_offset<F: Fn(Range<u32>) -> f64>(length: u32, target: u32, entropy: F) -> u32 {
+	let (mut start, mut end) = (0, length);
+	while end - start > target {
+		let step = (end - start - target).min(SLICE);
+		if entropy(start..start + step) < entropy(end - step..end) {
+			start += step;
+		} else {
+			end -= step;
+		}
+	}
+	start
+}
+
+/// Shannon entropy of the pixel values in the area.
+fn entropy(img: &GrayImage, xs: Range<u32>, ys: Range<u32>) -> f64 {
+	let mut histogram = [0u32; 256];
+	let mut total = 0;
+	for y in ys {
+		for x in xs.clone() {
+			histogram[img.get_pixel(x, y)[0] as usize] += 1;
+			total += 1;
+		}
+	}
+	histogram
+		.iter()
+		.filter(|&&count| count > 0)
+		.map(|&count| {
+			let p = count as f64 / total as f64;
+			-p * p.log2()
+		})
+		.sum()
+}
@@ -0,0 +1,51 @@
+pub mod crop;
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use image::{imageops::FilterType, io::Reader, DynamicImage, GenericImageView, ImageOutputFormat};
+
+use crate::api::config::transform::Transform;
+
+/// Directory inside the profile directory where untouched images are kept when the profile
+/// transforms images.
+pub const ORIGINALS_DIR: &str = ".originals";
+
+/// Crops the image to the aspect ratio, downscales it and encodes it in the output format.
+/// `format` is the format of the source image. Returns the encoded image.
+pub fn transform_file<P: AsRef<Path>>(
+	path: P,
+	format: &str,
+	transform: &Transform,
+	ratio: Option<(u32, u32)>,
+) -> Result<Vec<u8>> {
+	let path = path.as_ref();
+	let mut img = Reader::open(path)
+		.with_context(|| format!("failed to open file {}", path.display()))?
+		.with_guessed_format()?
+		.decode()
+		.with_context(|| format!("failed to decode image {}", path.display()))?;
+
+	if let Some(ratio) = ratio {
+		img = crop::crop(img, transform.crop, ratio);
+	}
+
+	let limit = |v: u32| if v == 0 { u32::MAX } else { v };
+	let (width, height) = (limit(transform.width), limit(transform.height));
+	if img.width() > width || img.height() > height {
+		img = img.resize(width, height, FilterType::Lanczos3);
+	}
+
+	let mut buf = Vec::new();
+	match transform.format.extension(format) {
+		"jpg" => {
+			// jpeg has no alpha channel
+			let quality = transform.quality.clamp(1, 100);
+			DynamicImage::ImageRgb8(img.to_rgb8())
+				.write_to(&mut buf, ImageOutputFormat::Jpeg(quality))?
+		}
+		"png" => img.write_to(&mut buf, ImageOutputFormat::Png)?,
+		other => bail!("transformed image cannot be encoded as {}", other),
+	}
+	Ok(buf)
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	reddit::OAuthFlow,
+};
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Auth {
+	/// Authenticates using a script app, as the reddit account that owns the app
+	///
+	/// Create a script app on https://www.reddit.com/prefs/apps. Note the password is stored in
+	/// plain text in the configuration file.
+	Script {
+		#[structopt(long)]
+		client_id: String,
+		#[structopt(long)]
+		client_secret: String,
+		#[structopt(long)]
+		username: String,
+		#[structopt(long)]
+		password: String,
+	},
+	/// Authenticates using an installed app, without a reddit account
+	///
+	/// Create an installed app on https://www.reddit.com/prefs/apps.
+	Installed {
+		#[structopt(long)]
+		client_id: String,
+	},
+	/// Disables authentication and uses anonymous access
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Shows the current authentication flow
+	Show,
+}
+
+impl Auth {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			Self::Script {
+				client_id,
+				client_secret,
+				username,
+				password,
+			} => {
+				config.reddit.flow = OAuthFlow::Script;
+				config.reddit.client_id = client_id.to_owned();
+				config.reddit.client_secret = client_secret.to_owned();
+				config.reddit.username = username.to_owned();
+				config.reddit.password = password.to_owned();
+				write_config(config).await?;
+			}
+			Self::Installed { client_id } => {
+				config.reddit.flow = OAuthFlow::Installed;
+				config.reddit.client_id = client_id.to_owned();
+				config.reddit.client_secret = String::new();
+				write_config(config).await?;
+			}
+			Self::Disable => {
+				config.reddit.flow = OAuthFlow::Anonymous;
+				write_config(config).await?;
+			}
+			Self::Show => {}
+		}
+		println!("reddit authentication flow is '{}'", config.reddit.flow);
+		Ok(())
+	}
+}
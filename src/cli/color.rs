@@ -0,0 +1,120 @@
+use anyhow::{bail, Result};
+use structopt::StructOpt;
+
+use crate::api::config::{
+	color::Color as RgbColor,
+	config::{write_config, Config},
+};
+
+/// Luminance bound used by `dark` and `light` shortcuts.
+const DARK_LUMINANCE: f32 = 0.4;
+const LIGHT_LUMINANCE: f32 = 0.6;
+
+#[derive(StructOpt, Debug, Clone)]
+pub enum Color {
+	/// Enables color filter for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables color filter for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Set accepted average luminance bounds (0.0-1.0) for focused profile
+	#[structopt(visible_alias = "lum")]
+	Luminance { min: f32, max: f32 },
+	/// Only accepts dark images for focused profile. Enables color filter
+	Dark,
+	/// Only accepts light images for focused profile. Enables color filter
+	Light,
+	/// Adds colors (e.g. `#1e90ff`) the image must contain one of for focused profile
+	#[structopt(visible_alias = "a")]
+	Add { input: Vec<RgbColor> },
+	/// Removes colors for focused profile
+	#[structopt(visible_alias = "rm")]
+	Remove { input: Vec<RgbColor> },
+	/// Set maximum distance (0-441) for colors to be considered near for focused profile
+	#[structopt(visible_alias = "dist")]
+	Distance { input: f32 },
+}
+
+impl Color {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			Color::Enable => self.enable(true, config).await?,
+			Color::Disable => self.enable(false, config).await?,
+			Color::Luminance { min, max } => self.luminance(*min, *max, false, config).await?,
+			Color::Dark => self.luminance(0.0, DARK_LUMINANCE, true, config).await?,
+			Color::Light => self.luminance(LIGHT_LUMINANCE, 1.0, true, config).await?,
+			Color::Add { input } => self.add(input, config).await?,
+			Color::Remove { input } => self.remove(input, config).await?,
+			Color::Distance { input } => self.distance(*input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, enable: bool, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.color.enable = enable;
+		write_config(config).await?;
+		println!(
+			"color filter {} for '{}'",
+			if enable { "enabled" } else { "disabled" },
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn luminance(&self, min: f32, max: f32, enable: bool, config: &mut Config) -> Result<()> {
+		if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) || min > max {
+			bail!("luminance bounds must be between 0.0 and 1.0, and min must not exceed max")
+		}
+		let cfg = config.get_mut_configuration()?;
+		cfg.color.min_luminance = min;
+		cfg.color.max_luminance = max;
+		cfg.color.enable |= enable;
+		write_config(config).await?;
+		println!(
+			"accepted luminance is set to '{}-{}' for '{}'",
+			min, max, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn add(&self, input: &[RgbColor], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		for color in input {
+			if !cfg.color.colors.contains(color) {
+				cfg.color.colors.push(*color);
+			}
+		}
+		write_config(config).await?;
+		self.print_colors(config)
+	}
+
+	async fn remove(&self, input: &[RgbColor], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.color.colors.retain(|color| !input.contains(color));
+		write_config(config).await?;
+		self.print_colors(config)
+	}
+
+	async fn distance(&self, input: f32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.color.distance = input;
+		write_config(config).await?;
+		println!(
+			"color distance is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	fn print_colors(&self, config: &Config) -> Result<()> {
+		let cfg = config.get_configuration()?;
+		let colors: Vec<String> = cfg.color.colors.iter().map(|c| c.to_string()).collect();
+		println!(
+			"accepted colors for '{}' is set to {:?}",
+			config.focused_profile, colors
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,144 @@
+use std::{
+	collections::{hash_map::Entry, HashMap},
+	fs::FileType,
+	path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use structopt::StructOpt;
+use tokio::fs;
+
+use crate::api::{
+	config::config::Config,
+	history::{database::History, hash::sha256_file},
+};
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Dedup {
+	/// Only prints the duplicates found without touching any file
+	#[structopt(long)]
+	dry_run: bool,
+	/// Replaces duplicates with hard links to the kept file instead of removing them
+	#[structopt(long)]
+	link: bool,
+}
+
+impl Dedup {
+	/// Scans every profile directory and removes files with the same content within the same
+	/// profile. The first file in path order is kept.
+	pub async fn handle(&self, config: &Config) -> Result<()> {
+		let history = History::open_default()?;
+		let mut total = 0;
+		for profile in config.settings.keys() {
+			let dir = config.path.join(profile);
+			if fs::metadata(&dir).await.is_err() {
+				continue;
+			}
+			let mut files = list_files(&dir).await?;
+			files.sort();
+
+			let mut seen: HashMap<String, PathBuf> = HashMap::new();
+			for file in files {
+				let hash = sha256_file(&file).await?;
+				let original = match seen.entry(hash) {
+					Entry::Occupied(entry) => entry.get().to_owned(),
+					Entry::Vacant(entry) => {
+						entry.insert(file);
+						continue;
+					}
+				};
+				if is_same_file(&original, &file).await {
+					continue;
+				}
+				total += 1;
+				self.resolve(&original, &file, &history).await?;
+			}
+		}
+		println!("found {} duplicate(s)", total);
+		Ok(())
+	}
+
+	async fn resolve(&self, original: &Path, duplicate: &Path, history: &History) -> Result<()> {
+		if self.dry_run {
+			println!(
+				"{} is a duplicate of {}",
+				duplicate.display(),
+				original.display()
+			);
+			return Ok(());
+		}
+		if self.link {
+			// the link replaces the duplicate in one rename, so the file is never lost when linking
+			// fails. The path keeps the same content, history stays valid.
+			let mut part = duplicate.as_os_str().to_owned();
+			part.push(".part");
+			let part = PathBuf::from(part);
+			fs::hard_link(original, &part).await.with_context(|| {
+				format!(
+					"failed to link {} to {}",
+					part.display(),
+					original.display()
+				)
+			})?;
+			if let Err(err) = fs::rename(&part, duplicate).await {
+				fs::remove_file(&part).await.ok();
+				return Err(err).with_context(|| {
+					format!("failed to replace duplicate {}", duplicate.display())
+				});
+			}
+			println!("linked {} to {}", duplicate.display(), original.display());
+			return Ok(());
+		}
+		fs::remove_file(duplicate)
+			.await
+			.with_context(|| format!("failed to remove duplicate {}", duplicate.display()))?;
+		history.remove_file_path(duplicate)?;
+		history.remove_perceptual_hash(duplicate)?;
+		println!("removed {}", duplicate.display());
+		Ok(())
+	}
+}
+
+/// Lists regular files under the directory recursively.
+pub async fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+	walk(dir, |file_type| file_type.is_file()).await
+}
+
+/// Lists symbolic links under the directory recursively.
+pub async fn list_symlinks(dir: &Path) -> Result<Vec<PathBuf>> {
+	walk(dir, |file_type| file_type.is_symlink()).await
+}
+
+async fn walk<F: Fn(&FileType) -> bool>(dir: &Path, accept: F) -> Result<Vec<PathBuf>> {
+	let mut result = Vec::new();
+	let mut dirs = vec![dir.to_path_buf()];
+	while let Some(dir) = dirs.pop() {
+		let mut entries = fs::read_dir(&dir)
+			.await
+			.with_context(|| format!("failed to read directory {}", dir.display()))?;
+		while let Some(entry) = entries.next_entry().await? {
+			let file_type = entry.file_type().await?;
+			if file_type.is_dir() {
+				dirs.push(entry.path());
+			} else if accept(&file_type) {
+				result.push(entry.path());
+			}
+		}
+	}
+	Ok(result)
+}
+
+/// Checks if both paths are hard links to the same file.
+#[cfg(unix)]
+async fn is_same_file(a: &Path, b: &Path) -> bool {
+	use std::os::unix::fs::MetadataExt;
+	match (fs::metadata(a).await, fs::metadata(b).await) {
+		(Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+		_ => false,
+	}
+}
+
+#[cfg(not(unix))]
+async fn is_same_file(_: &Path, _: &Path) -> bool {
+	false
+}
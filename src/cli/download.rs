@@ -2,7 +2,10 @@ use anyhow::Result;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
-use crate::api::config::config::{write_config, Config};
+use crate::api::config::{
+	config::{write_config, Config},
+	configuration::{DuplicateStrategy, StorageStrategy},
+};
 
 #[derive(StructOpt, Debug, Clone)]
 pub enum Download {
@@ -18,6 +21,43 @@ pub enum Download {
 	/// Sets the download threads
 	#[structopt(visible_aliases = &["thr", "thread"])]
 	Threads { input: usize },
+	/// Sets how to handle images with the same content as an already stored image in a profile
+	///
+	/// Valid values are `skip` (default), `hardlink` and `keep`.
+	#[structopt(visible_alias = "dup")]
+	Duplicate { input: DuplicateStrategy },
+	/// Sets how to store an image that matches more than one profile
+	///
+	/// Valid values are `copy` (default), `hardlink`, `symlink` and `reflink`. Falls back to copy
+	/// when the filesystem does not support it.
+	///
+	/// Prefer `hardlink` over `symlink`. Symbolic links break when the image is deleted from the
+	/// first profile, run `ridit verify` to find them.
+	Storage { input: StorageStrategy },
+	/// Sets file naming template, relative to the profile directory
+	///
+	/// Supported placeholders: `{subreddit}`, `{post_id}`, `{title}`, `{author}`, `{width}`,
+	/// `{height}`, `{date}`, `{filename}`, `{name}` and `{ext}`. Use `/` to create directories.
+	///
+	/// Example: `ridit download filename-template "{subreddit}/{date} {title} ({width}x{height}).{ext}"`
+	#[structopt(visible_alias = "template")]
+	FilenameTemplate { input: String },
+	/// Embeds post information (title, author, permalink, etc.) as XMP metadata into jpeg and png
+	/// images
+	EmbedMetadata {
+		#[structopt(parse(try_from_str))]
+		input: bool,
+	},
+	/// Writes post information (title, author, permalink, etc.) to `{image}.json` file next to
+	/// each downloaded image
+	MetadataSidecar {
+		#[structopt(parse(try_from_str))]
+		input: bool,
+	},
+	/// Sets imgur API client id. Required to download imgur albums
+	///
+	/// Register an application on https://api.imgur.com/oauth2/addclient to get one.
+	ImgurClientId { input: String },
 }
 
 impl Download {
@@ -26,6 +66,12 @@ impl Download {
 			Self::Path { input } => Download::path(input, config).await?,
 			Self::ConnectTimeout { input } => Download::connect_timeout(*input, config).await?,
 			Self::Threads { input } => Download::threads(*input, config).await?,
+			Self::Duplicate { input } => Download::duplicate(*input, config).await?,
+			Self::Storage { input } => Download::storage(*input, config).await?,
+			Self::FilenameTemplate { input } => Download::filename_template(input, config).await?,
+			Self::EmbedMetadata { input } => Download::embed_metadata(*input, config).await?,
+			Self::MetadataSidecar { input } => Download::metadata_sidecar(*input, config).await?,
+			Self::ImgurClientId { input } => Download::imgur_client_id(input, config).await?,
 		})
 	}
 
@@ -50,4 +96,46 @@ impl Download {
 		println!("download thread is set to {} threads", input);
 		Ok(())
 	}
+
+	async fn duplicate(input: DuplicateStrategy, config: &mut Config) -> Result<()> {
+		config.duplicate = input;
+		write_config(config).await?;
+		println!("duplicate strategy is set to {}", input);
+		Ok(())
+	}
+
+	async fn storage(input: StorageStrategy, config: &mut Config) -> Result<()> {
+		config.storage = input;
+		write_config(config).await?;
+		println!("storage strategy is set to {}", input);
+		Ok(())
+	}
+
+	async fn filename_template(input: &str, config: &mut Config) -> Result<()> {
+		config.filename_template = input.to_string();
+		write_config(config).await?;
+		println!("filename template is set to {}", input);
+		Ok(())
+	}
+
+	async fn embed_metadata(input: bool, config: &mut Config) -> Result<()> {
+		config.metadata.embed = input;
+		write_config(config).await?;
+		println!("embed metadata is set to {}", input);
+		Ok(())
+	}
+
+	async fn metadata_sidecar(input: bool, config: &mut Config) -> Result<()> {
+		config.metadata.sidecar = input;
+		write_config(config).await?;
+		println!("metadata sidecar is set to {}", input);
+		Ok(())
+	}
+
+	async fn imgur_client_id(input: &str, config: &mut Config) -> Result<()> {
+		config.imgur.client_id = input.to_string();
+		write_config(config).await?;
+		println!("imgur client id is set");
+		Ok(())
+	}
 }
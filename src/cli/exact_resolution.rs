@@ -0,0 +1,113 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	configuration::Resolution,
+};
+
+#[derive(StructOpt, Debug, Clone)]
+pub enum ExactResolution {
+	/// Enables exact resolution check for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables exact resolution check for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Set how many pixels width and height may differ from the resolution for focused profile
+	#[structopt(visible_alias = "t")]
+	Tolerance { input: u32 },
+	/// Replaces accepted resolutions for focused profile
+	#[structopt(visible_alias = "s")]
+	Set { input: Vec<Resolution> },
+	/// Adds accepted resolutions for focused profile
+	#[structopt(visible_alias = "a")]
+	Add { input: Vec<Resolution> },
+	/// Removes accepted resolutions for focused profile
+	#[structopt(visible_alias = "rm")]
+	Remove { input: Vec<Resolution> },
+}
+
+impl ExactResolution {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			ExactResolution::Enable => self.enable(true, config).await?,
+			ExactResolution::Disable => self.enable(false, config).await?,
+			ExactResolution::Tolerance { input } => self.tolerance(*input, config).await?,
+			ExactResolution::Set { input } => self.set(input, config).await?,
+			ExactResolution::Add { input } => self.add(input, config).await?,
+			ExactResolution::Remove { input } => self.remove(input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, enable: bool, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.exact_resolution.enable = enable;
+		write_config(config).await?;
+		println!(
+			"exact resolution check {} for '{}'",
+			if enable { "enabled" } else { "disabled" },
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn tolerance(&self, input: u32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.exact_resolution.tolerance = input;
+		write_config(config).await?;
+		println!(
+			"exact resolution tolerance is set to '{}' pixels for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn set(&self, input: &[Resolution], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.exact_resolution.resolutions.clear();
+		for res in input {
+			if !cfg.exact_resolution.resolutions.contains(res) {
+				cfg.exact_resolution.resolutions.push(*res);
+			}
+		}
+		write_config(config).await?;
+		self.print_resolutions(config)
+	}
+
+	async fn add(&self, input: &[Resolution], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		for res in input {
+			if !cfg.exact_resolution.resolutions.contains(res) {
+				cfg.exact_resolution.resolutions.push(*res);
+			}
+		}
+		write_config(config).await?;
+		self.print_resolutions(config)
+	}
+
+	async fn remove(&self, input: &[Resolution], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.exact_resolution
+			.resolutions
+			.retain(|res| !input.contains(res));
+		write_config(config).await?;
+		self.print_resolutions(config)
+	}
+
+	fn print_resolutions(&self, config: &Config) -> Result<()> {
+		let cfg = config.get_configuration()?;
+		let resolutions: Vec<String> = cfg
+			.exact_resolution
+			.resolutions
+			.iter()
+			.map(|res| res.to_string())
+			.collect();
+		println!(
+			"accepted resolutions for '{}' is set to {:?}",
+			config.focused_profile, resolutions
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::config::{write_config, Config};
+
+#[derive(StructOpt, Debug, Clone, Copy)]
+pub enum FileSize {
+	/// Enables file size check for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables file size check for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Set minimum file size for focused profile, e.g. `500KB`, `1MB`, or plain bytes
+	#[structopt(visible_alias = "min")]
+	Minimum {
+		#[structopt(parse(try_from_str = parse_byte_size))]
+		input: u64,
+	},
+	/// Set maximum file size for focused profile, e.g. `20MB`. 0 means no limit
+	#[structopt(visible_alias = "max")]
+	Maximum {
+		#[structopt(parse(try_from_str = parse_byte_size))]
+		input: u64,
+	},
+}
+
+impl FileSize {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			FileSize::Enable => self.enable(true, config).await?,
+			FileSize::Disable => self.enable(false, config).await?,
+			FileSize::Minimum { input } => self.minimum(*input, config).await?,
+			FileSize::Maximum { input } => self.maximum(*input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, enable: bool, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.file_size.enable = enable;
+		write_config(config).await?;
+		println!(
+			"file size check {} for '{}'",
+			if enable { "enabled" } else { "disabled" },
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn minimum(&self, input: u64, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.file_size.minimum = input;
+		write_config(config).await?;
+		println!(
+			"minimum file size is set to '{}' bytes for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn maximum(&self, input: u64, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.file_size.maximum = input;
+		write_config(config).await?;
+		println!(
+			"maximum file size is set to '{}' bytes for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+}
+
+/// Parses size like `500`, `500B`, `300KB`, `1.5MB` or `1GB`. Units are 1024 based.
+fn parse_byte_size(s: &str) -> Result<u64, String> {
+	let s = s.trim().to_uppercase();
+	let split = s
+		.find(|c: char| !c.is_ascii_digit() && c != '.')
+		.unwrap_or(s.len());
+	let (number, unit) = s.split_at(split);
+	let number: f64 = number
+		.parse()
+		.map_err(|_| format!("'{}' is not a valid size", s))?;
+	let multiplier = match unit.trim() {
+		"" | "B" => 1,
+		"K" | "KB" | "KIB" => 1024,
+		"M" | "MB" | "MIB" => 1024 * 1024,
+		"G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+		unit => return Err(format!("unknown size unit '{}'", unit)),
+	};
+	Ok((number * multiplier as f64) as u64)
+}
@@ -0,0 +1,91 @@
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Result};
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	filter::{Keyword, PostFilter},
+};
+
+/// Post filter options. Repeatable options accept one value each time, e.g.
+/// `--keyword mountain --keyword lake`.
+#[derive(Debug, StructOpt, Clone, Default)]
+pub struct FilterOpts {
+	/// Only accepts posts with title containing this keyword. Wrap in slashes for regex, e.g. `/^\[OC\]/`
+	#[structopt(long = "keyword", number_of_values = 1)]
+	keywords: Vec<String>,
+	/// Rejects posts with title containing this keyword. Wrap in slashes for regex
+	#[structopt(long = "exclude-keyword", number_of_values = 1)]
+	exclude_keywords: Vec<String>,
+	/// Only accepts posts with this link flair
+	#[structopt(long = "flair", number_of_values = 1)]
+	flairs: Vec<String>,
+	/// Rejects posts with this link flair
+	#[structopt(long = "exclude-flair", number_of_values = 1)]
+	exclude_flairs: Vec<String>,
+	/// Only accepts posts from this author
+	#[structopt(long = "author", number_of_values = 1)]
+	authors: Vec<String>,
+	/// Rejects posts from this author
+	#[structopt(long = "exclude-author", number_of_values = 1)]
+	exclude_authors: Vec<String>,
+	/// Rejects posts with score lower than this
+	#[structopt(long, allow_hyphen_values = true)]
+	min_score: Option<i64>,
+	/// Rejects posts with upvote ratio lower than this, between 0 and 1
+	#[structopt(long, default_value = "0")]
+	min_upvote_ratio: f32,
+	/// Rejects posts with less comments than this
+	#[structopt(long, default_value = "0")]
+	min_comments: u32,
+}
+
+impl FilterOpts {
+	/// Converts options into filter. Fails on invalid regex keyword.
+	pub fn to_filter(&self) -> Result<PostFilter> {
+		let keywords = |list: &[String]| -> Result<Vec<Keyword>> {
+			list.iter()
+				.map(|k| Keyword::try_from(k.to_owned()).map_err(|err| anyhow!(err)))
+				.collect()
+		};
+		Ok(PostFilter {
+			keywords: keywords(&self.keywords)?,
+			exclude_keywords: keywords(&self.exclude_keywords)?,
+			flairs: self.flairs.to_owned(),
+			exclude_flairs: self.exclude_flairs.to_owned(),
+			authors: self.authors.to_owned(),
+			exclude_authors: self.exclude_authors.to_owned(),
+			min_score: self.min_score,
+			min_upvote_ratio: self.min_upvote_ratio,
+			min_comments: self.min_comments,
+		})
+	}
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Filter {
+	/// Replaces post filter of focused profile
+	#[structopt(visible_alias = "s")]
+	Set(FilterOpts),
+	/// Removes every post filter rule of focused profile
+	Clear,
+}
+
+impl Filter {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		let filter = match self {
+			Filter::Set(opts) => opts.to_filter()?,
+			Filter::Clear => PostFilter::default(),
+		};
+		let cfg = config.get_mut_configuration()?;
+		cfg.filter = filter;
+		write_config(config).await?;
+		println!(
+			"post filter of '{}' is set to:\n{}",
+			config.focused_profile,
+			toml::to_string_pretty(&config.get_configuration()?.filter)?
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	configuration::normalize_format,
+};
+
+#[derive(StructOpt, Debug, Clone)]
+pub enum Formats {
+	/// Replaces accepted image formats for focused profile
+	#[structopt(visible_alias = "s")]
+	Set { input: Vec<String> },
+	/// Adds accepted image formats for focused profile
+	#[structopt(visible_alias = "a")]
+	Add { input: Vec<String> },
+	/// Removes accepted image formats for focused profile
+	#[structopt(visible_alias = "rm")]
+	Remove { input: Vec<String> },
+}
+
+impl Formats {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			Formats::Set { input } => self.set(input, config).await?,
+			Formats::Add { input } => self.add(input, config).await?,
+			Formats::Remove { input } => self.remove(input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn set(&self, input: &[String], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.formats = input.iter().map(|f| normalize_format(f)).collect();
+		cfg.formats.dedup();
+		write_config(config).await?;
+		self.print_formats(config)
+	}
+
+	async fn add(&self, input: &[String], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		for format in input {
+			if !cfg.accepts_format(format) {
+				cfg.formats.push(normalize_format(format));
+			}
+		}
+		write_config(config).await?;
+		self.print_formats(config)
+	}
+
+	async fn remove(&self, input: &[String], config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		let input: Vec<String> = input.iter().map(|f| normalize_format(f)).collect();
+		cfg.formats
+			.retain(|format| !input.contains(&normalize_format(format)));
+		write_config(config).await?;
+		self.print_formats(config)
+	}
+
+	fn print_formats(&self, config: &Config) -> Result<()> {
+		let cfg = config.get_configuration()?;
+		println!(
+			"accepted image formats for '{}' is set to {:?}",
+			config.focused_profile, cfg.formats
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::config::{write_config, Config};
+
+#[derive(StructOpt, Debug, Clone, Copy)]
+pub enum MaximumSize {
+	/// Enables maximum size check for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables maximum size check for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Set maximum size height for focused profile
+	#[structopt(visible_alias = "h")]
+	Height { input: u32 },
+	/// Set maximum size width for focused profile
+	#[structopt(visible_alias = "w")]
+	Width { input: u32 },
+}
+
+impl MaximumSize {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			MaximumSize::Enable => self.enable(config).await?,
+			MaximumSize::Disable => self.disable(config).await?,
+			MaximumSize::Height { input } => self.height(*input, config).await?,
+			MaximumSize::Width { input } => self.width(*input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.maximum_size.enable = true;
+		write_config(config).await?;
+		println!(
+			"maximum size check enabled for '{}'",
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn disable(&self, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.maximum_size.enable = false;
+		write_config(config).await?;
+		println!(
+			"maximum size check disabled for '{}'",
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn height(&self, input: u32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.maximum_size.height = input;
+		write_config(config).await?;
+		println!(
+			"maximum size height is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn width(&self, input: u32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.maximum_size.width = input;
+		write_config(config).await?;
+		println!(
+			"maximum size width is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+}
@@ -1,11 +1,23 @@
 pub mod aspect_ratio;
+pub mod auth;
+pub mod color;
+pub mod dedup;
 pub mod download;
+pub mod exact_resolution;
+pub mod file_size;
+pub mod filter;
+pub mod formats;
+pub mod maximum_size;
 pub mod minimum_size;
+pub mod near_duplicate;
 pub mod print;
 pub mod profile;
 pub mod server;
+pub mod source;
 pub mod start;
 pub mod subreddit;
+pub mod transform;
+pub mod verify;
 
 use anyhow::Result;
 use structopt::{
@@ -41,6 +53,10 @@ impl Opt {
 			SubCommand::Start => start::start(&config).await?,
 			SubCommand::Print(p) => p.print(&config)?,
 			SubCommand::Server(cmd) => cmd.handle(config).await?,
+			SubCommand::Dedup(dedup) => dedup.handle(&config).await?,
+			SubCommand::Source(source) => source.handle(&mut config).await?,
+			SubCommand::Auth(auth) => auth.handle(&mut config).await?,
+			SubCommand::Verify(verify) => verify.handle(&config).await?,
 		}
 		Ok(())
 	}
@@ -55,8 +71,14 @@ pub enum SubCommand {
 	/// Example adding subreddits while filtering content rated as nsfw:
 	/// `ridit subreddit add --no-nsfw wallpaper wallpapers`
 	Subreddit(subreddit::Subreddit),
+	/// Add or remove image sources other than subreddits, like local folders.
+	Source(source::Source),
 	/// Configures download settings.
 	Download(download::Download),
+	/// Configures reddit API authentication (OAuth2).
+	///
+	/// Anonymous access is heavily rate limited. Authenticated requests are sent to oauth.reddit.com
+	Auth(auth::Auth),
 	/// Start the download manually
 	Start,
 	/// Prints whole configuration
@@ -67,4 +89,12 @@ pub enum SubCommand {
 	Server(ServerCMD),
 	/// Sets profile specific configuration like aspect ratio and minimum size check
 	Profile(profile::Profile),
+	/// Finds and removes images with the same content within each profile
+	///
+	/// Example replacing duplicates with hard links: `ridit dedup --link`
+	Dedup(dedup::Dedup),
+	/// Finds truncated or broken images and moves them to quarantine directory
+	///
+	/// Quarantined images are removed from download history, so they will be downloaded again.
+	Verify(verify::Verify),
 }
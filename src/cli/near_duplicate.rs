@@ -0,0 +1,61 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::api::config::config::{write_config, Config};
+
+#[derive(StructOpt, Debug, Clone, Copy)]
+pub enum NearDuplicate {
+	/// Enables near duplicate check for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables near duplicate check for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Set maximum hash distance (0-64) for images to be considered the same for focused profile
+	#[structopt(visible_alias = "dist")]
+	Distance { input: u32 },
+}
+
+impl NearDuplicate {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			NearDuplicate::Enable => self.enable(config).await?,
+			NearDuplicate::Disable => self.disable(config).await?,
+			NearDuplicate::Distance { input } => self.distance(*input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.near_duplicate.enable = true;
+		write_config(config).await?;
+		println!(
+			"near duplicate check enabled for '{}'",
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn disable(&self, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.near_duplicate.enable = false;
+		write_config(config).await?;
+		println!(
+			"near duplicate check disabled for '{}'",
+			config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn distance(&self, input: u32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.near_duplicate.distance = input;
+		write_config(config).await?;
+		println!(
+			"near duplicate distance is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+}
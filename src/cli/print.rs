@@ -13,14 +13,17 @@ pub struct Print {
 }
 
 impl Print {
+	/// Prints the configuration. Reddit password and client secret are masked.
 	pub fn print(&self, config: &Config) -> Result<()> {
+		let mut config = config.clone();
+		config.reddit = config.reddit.masked();
 		Ok(match self.format {
 			OutFormat::JSON => {
-				let val = serde_json::to_string_pretty(config)?;
+				let val = serde_json::to_string_pretty(&config)?;
 				println!("{}", val);
 			}
 			OutFormat::TOML => {
-				let val = toml::to_string_pretty(config)?;
+				let val = toml::to_string_pretty(&config)?;
 				println!("{}", val);
 			}
 		})
@@ -3,10 +3,15 @@ use structopt::StructOpt;
 
 use crate::api::config::{
 	config::{write_config, Config},
-	configuration::Configuration,
+	configuration::{Configuration, DownloadSize, Orientation},
 };
 
-use super::{aspect_ratio::AspectRatio, minimum_size::MinimumSize, subreddit::OutFormat, Format};
+use super::{
+	aspect_ratio::AspectRatio, color::Color, exact_resolution::ExactResolution,
+	file_size::FileSize, filter::Filter, formats::Formats, maximum_size::MaximumSize,
+	minimum_size::MinimumSize, near_duplicate::NearDuplicate, subreddit::OutFormat,
+	transform::Transform, Format,
+};
 
 #[derive(Debug, StructOpt, Clone)]
 pub enum Profile {
@@ -59,6 +64,81 @@ pub enum Profile {
 	Remove { profile_name: String },
 	/// Configures minimum size image checks for current profile
 	MinimumSize(MinimumSize),
+	/// Configures maximum size image checks for current profile
+	///
+	/// Useful to reject very large images like panoramas that some wallpaper programs cannot
+	/// handle. Disabled by default.
+	MaximumSize(MaximumSize),
+	/// Configures exact resolution checks for current profile
+	///
+	/// When enabled, only images with one of the accepted resolutions are downloaded. Tolerance
+	/// sets how many pixels width and height may differ from the resolution.
+	///
+	/// Example: `ridit profile exact-resolution add 1920x1080 2560x1440`
+	ExactResolution(ExactResolution),
+	/// Configures file size checks for current profile
+	///
+	/// File size is checked against the size reported by the server before download, and the
+	/// actual file size after download.
+	///
+	/// Example: `ridit profile file-size minimum 300KB`
+	FileSize(FileSize),
+	/// Sets accepted image orientation for current profile
+	///
+	/// Valid values are `any` (default), `landscape`, `portrait` and `square`.
+	Orientation { input: Orientation },
+	/// Sets which size of the image is downloaded for current profile
+	///
+	/// `source` (default) downloads the full size image. A resolution like `1080x2400` downloads
+	/// the smallest version Reddit has that is at least that large, falling back to the source
+	/// image. Useful for small devices like phones, where the full size image is a waste of space.
+	///
+	/// When an image fits more than one profile, the largest size of them is downloaded.
+	DownloadSize { input: DownloadSize },
+	/// Configures post filter for current profile
+	///
+	/// Posts are filtered by title keywords, flair, author, score, upvote ratio and comment count.
+	///
+	/// Example: `ridit profile filter set --exclude-keyword meme --min-score 50`
+	Filter(Filter),
+	/// Configures near duplicate checks for current profile
+	///
+	/// When enabled, images that look like an already downloaded image in the profile
+	/// (re-encoded or resized reposts) are rejected. If the new image has higher resolution, the
+	/// old image is replaced instead.
+	///
+	/// Distance controls how similar the images must be. 0 means exactly the same looking image,
+	/// the default of 5 catches most re-encodes and resizes.
+	NearDuplicate(NearDuplicate),
+	/// Configures color filter for current profile
+	///
+	/// Dominant colors and average luminance are computed from every downloaded image. When
+	/// enabled, images outside the luminance bounds, or without a color near one of the accepted
+	/// colors, are rejected after download.
+	///
+	/// Example: `ridit profile color dark` or `ridit profile color add "#1e90ff"`
+	Color(Color),
+	/// Configures image transform for current profile
+	///
+	/// When enabled, downloaded images are cropped to the aspect ratio of the profile, downscaled
+	/// to fit the size and re-encoded before they are stored. Useful to store images ready to use
+	/// on the device. Only jpeg and png images can be transformed, other formats are stored as is.
+	///
+	/// Example: `ridit profile transform size 1080 2400`
+	Transform(Transform),
+	/// Configures accepted image formats for current profile
+	///
+	/// Formats are checked against the url extension, the response content type and the file
+	/// content itself. `jpg` and `jpeg` are treated as the same format.
+	///
+	/// Example: `ridit profile formats set jpg png webp`
+	Formats(Formats),
+	/// Overrides file naming template for current profile
+	///
+	/// Run without template to use the global template again. See `ridit download
+	/// filename-template --help` for supported placeholders.
+	#[structopt(visible_alias = "template")]
+	FilenameTemplate { template: Option<String> },
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -98,6 +178,19 @@ impl Profile {
 			Profile::Add(ao) => self.add_profile(ao, config).await?,
 			Profile::Remove { profile_name } => self.remove_profile(profile_name, config).await?,
 			Profile::MinimumSize(ms) => ms.handle(config).await?,
+			Profile::MaximumSize(ms) => ms.handle(config).await?,
+			Profile::ExactResolution(er) => er.handle(config).await?,
+			Profile::FileSize(fs) => fs.handle(config).await?,
+			Profile::Filter(f) => f.handle(config).await?,
+			Profile::Orientation { input } => self.orientation(*input, config).await?,
+			Profile::DownloadSize { input } => self.download_size(*input, config).await?,
+			Profile::NearDuplicate(nd) => nd.handle(config).await?,
+			Profile::Color(c) => c.handle(config).await?,
+			Profile::Transform(t) => t.handle(config).await?,
+			Profile::Formats(f) => f.handle(config).await?,
+			Profile::FilenameTemplate { template } => {
+				self.filename_template(template.as_deref(), config).await?
+			}
 		};
 		Ok(())
 	}
@@ -140,6 +233,45 @@ impl Profile {
 		Ok(())
 	}
 
+	async fn orientation(&self, input: Orientation, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.orientation = input;
+		write_config(config).await?;
+		println!(
+			"orientation is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn download_size(&self, input: DownloadSize, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.download_size = input;
+		write_config(config).await?;
+		println!(
+			"download size is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn filename_template(&self, input: Option<&str>, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.filename_template = input.unwrap_or_default().to_string();
+		write_config(config).await?;
+		match input {
+			Some(template) => println!(
+				"filename template of '{}' is set to {}",
+				config.focused_profile, template
+			),
+			None => println!(
+				"filename template of '{}' is set to global template",
+				config.focused_profile
+			),
+		}
+		Ok(())
+	}
+
 	async fn remove_profile(&self, input: &str, config: &mut Config) -> Result<()> {
 		if let None = config.remove(input) {
 			bail!("profile '{}' does not exist in configuration", input)
@@ -11,8 +11,16 @@ use crate::{
 #[derive(Debug, Clone, StructOpt)]
 pub enum ServerCMD {
 	Start,
-	Port { port: u16 },
-	IP { ip_addr: IpAddr },
+	Port {
+		port: u16,
+	},
+	IP {
+		ip_addr: IpAddr,
+	},
+	/// Sets the interval (in minutes) between scheduled downloads. 0 disables scheduled download
+	Interval {
+		minutes: u32,
+	},
 }
 
 impl ServerCMD {
@@ -21,6 +29,7 @@ impl ServerCMD {
 			ServerCMD::Start => self.start(config).await?,
 			ServerCMD::Port { port } => self.port(port, &mut config).await?,
 			ServerCMD::IP { ip_addr } => self.ip(ip_addr, &mut config).await?,
+			ServerCMD::Interval { minutes } => self.interval(minutes, &mut config).await?,
 		}
 		Ok(())
 	}
@@ -47,4 +56,15 @@ impl ServerCMD {
 		println!("server ip is set to {}", ip);
 		Ok(())
 	}
+
+	async fn interval(&self, minutes: u32, config: &mut Config) -> Result<()> {
+		config.server.download_interval = minutes;
+		write_config(config).await?;
+		if minutes == 0 {
+			println!("server scheduled download is disabled");
+		} else {
+			println!("server download interval is set to {} minutes", minutes);
+		}
+		Ok(())
+	}
 }
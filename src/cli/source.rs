@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	source::SourceConfig,
+};
+
+use super::{subreddit::OutFormat, Format};
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Source {
+	/// Add (or replace) a local folder to import images from
+	///
+	/// Images in the folder are checked against every profile like subreddit images, and
+	/// stored under a directory with the source name.
+	///
+	/// Example: `ridit source add-folder phone ~/Downloads/wallpapers`
+	AddFolder {
+		name: String,
+		#[structopt(parse(from_os_str))]
+		path: PathBuf,
+	},
+	/// Remove source(s)
+	#[structopt(visible_aliases = &["delete", "rm"])]
+	Remove { input: Vec<String> },
+	/// List added sources
+	#[structopt(visible_alias = "ls")]
+	List(Format),
+}
+
+impl Source {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			Self::AddFolder { name, path } => Self::add_folder(name, path, config).await?,
+			Self::Remove { input } => Self::remove(input, config).await?,
+			Self::List(opts) => Self::list(opts, config)?,
+		}
+		Ok(())
+	}
+
+	async fn add_folder(name: &str, path: &Path, config: &mut Config) -> Result<()> {
+		let path = path
+			.canonicalize()
+			.with_context(|| format!("folder {} does not exist", path.display()))?;
+		config.sources.insert(
+			name.to_string(),
+			SourceConfig::Folder { path: path.clone() },
+		);
+		write_config(config).await?;
+		println!("added folder source '{}' from {}", name, path.display());
+		Ok(())
+	}
+
+	async fn remove(input: &[String], config: &mut Config) -> Result<()> {
+		if input.is_empty() {
+			bail!("no source specified to remove")
+		}
+		let mut result = vec![];
+		for name in input {
+			match config.sources.remove(name) {
+				Some(_) => result.push(name.to_owned()),
+				None => println!("source {} does not exist in configuration", name),
+			}
+		}
+		write_config(config).await?;
+		println!("removed sources: {:?}", result);
+		Ok(())
+	}
+
+	fn list(opts: &Format, config: &Config) -> Result<()> {
+		let text = match opts.format {
+			OutFormat::JSON => serde_json::to_string_pretty(&config.sources)
+				.context("failed to serialize sources to json format")?,
+			OutFormat::TOML => toml::to_string_pretty(&config.sources)
+				.context("failed to serialize sources to toml format")?,
+		};
+		println!("{}", text);
+		Ok(())
+	}
+}
@@ -18,7 +18,7 @@ use twox_hash::RandomXxHashBuilder64;
 /// Start downloading once
 pub async fn start(config: &Config) -> Result<()> {
 	let config = Arc::new(config.to_owned());
-	let repo = Repository::new(config);
+	let repo = Repository::new(config)?;
 
 	let text = if atty::is(Stream::Stdout) {
 		PrintOut::Bar
@@ -86,6 +86,14 @@ async fn display_text(mut rx: UnboundedReceiver<DownloadStatus>) {
 			v_err.push(status);
 			continue;
 		}
+		if let Some(reason) = &status.rejected {
+			println!("{} rejected: {}", status.cli_label(), reason);
+			continue;
+		}
+		if let Some(warning) = &status.warning {
+			println!("{} warning: {}", status.cli_label(), warning);
+			continue;
+		}
 		if status.finished {
 			println!("{} finished", status.cli_label());
 			continue;
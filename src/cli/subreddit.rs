@@ -1,10 +1,11 @@
-use std::{convert::Infallible, fmt::Display, str::FromStr};
+use std::{convert::Infallible, fmt::Display, str::FromStr, sync::Arc};
 use structopt::StructOpt;
 
 use crate::api::{
 	config::{
 		config::{write_config, Config},
-		configuration::Sort,
+		configuration::{Sort, TimeRange},
+		policy::{CrosspostPolicy, Policy, PostPolicy},
 	},
 	reddit::repository::Repository,
 };
@@ -12,7 +13,7 @@ use anyhow::{bail, Context, Error, Result};
 
 use crate::api::config::configuration::Subreddit as SubredditConf;
 
-use super::Format;
+use super::{filter::FilterOpts, Format};
 
 #[derive(Debug, StructOpt, Clone)]
 pub enum Subreddit {
@@ -22,7 +23,7 @@ pub enum Subreddit {
 	///
 	/// adding subreddit using default settings: `ridit subreddit add wallpaper wallpapers`
 	#[structopt(visible_aliases = &["insert", "update"])]
-	Add(AddSubreddit),
+	Add(Box<AddSubreddit>),
 	/// Remove subreddit(s) from subscription
 	#[structopt(visible_aliases = &["delete", "rm"])]
 	Remove(InputOnly),
@@ -48,15 +49,34 @@ impl Subreddit {
 		conf.nsfw = !add.no_nsfw;
 		conf.download_first = add.download_first;
 		conf.sort = add.sort;
+		conf.time = add.time;
+		conf.max_pages = add.max_pages;
+		for profile in add.profiles.iter().chain(add.exclude_profiles.iter()) {
+			if config.get(profile).is_none() {
+				bail!("profile '{}' does not exist in configuration", profile)
+			}
+		}
+		conf.profiles = add.profiles.to_owned();
+		conf.exclude_profiles = add.exclude_profiles.to_owned();
+		conf.filter = add.filter.to_filter()?;
+		conf.policy = PostPolicy {
+			stickied: add.stickied,
+			removed: add.removed,
+			deleted: add.deleted,
+			spoiler: add.spoiler,
+			crosspost: add.crosspost,
+		};
+		let repo = Repository::new(Arc::new(config.clone()))?;
 		let mut handlers = Vec::new();
 		for name in &add.input {
 			let exist = config.subreddits.get(name).is_some();
 			let mut name = name.to_owned();
+			let repo = repo.clone();
 			let handler = tokio::spawn(async move {
 				if exist {
 					return (name, Ok::<bool, Error>(true));
 				}
-				let result = Repository::subreddit_exist(&mut name).await;
+				let result = repo.subreddit_exist(&mut name).await;
 				(name, result)
 			});
 			handlers.push(handler);
@@ -142,6 +162,55 @@ pub struct AddSubreddit {
 	/// Sets the sort method. defaults to `new`
 	#[structopt(short, long, default_value = "new")]
 	sort: Sort,
+
+	/// Sets the time window for `top` and `controversial` sort. defaults to `day`
+	///
+	/// Valid values are `hour`, `day`, `week`, `month`, `year` and `all`.
+	#[structopt(short, long, default_value = "day")]
+	time: TimeRange,
+
+	/// Sets how many listing pages (100 posts each) to fetch per download. defaults to `1`
+	///
+	/// When sort is `new`, fetching stops early once a page contains an already downloaded post.
+	#[structopt(short, long, default_value = "1")]
+	max_pages: u32,
+
+	/// Only stores images from the subreddit(s) to this profile. Can be repeated.
+	///
+	/// Example: `ridit subreddit add --profile mobile MobileWallpaper`
+	#[structopt(short, long = "profile", number_of_values = 1)]
+	profiles: Vec<String>,
+
+	/// Never stores images from the subreddit(s) to this profile. Can be repeated.
+	#[structopt(short, long = "exclude-profile", number_of_values = 1)]
+	exclude_profiles: Vec<String>,
+
+	#[structopt(flatten)]
+	filter: FilterOpts,
+
+	/// Sets how to treat stickied posts. Valid values are `allow` and `skip`
+	#[structopt(long, default_value = "skip")]
+	stickied: Policy,
+
+	/// Sets how to treat posts removed by moderators or reddit. Valid values are `allow` and `skip`
+	#[structopt(long, default_value = "skip")]
+	removed: Policy,
+
+	/// Sets how to treat posts with deleted author. Valid values are `allow` and `skip`
+	#[structopt(long, default_value = "skip")]
+	deleted: Policy,
+
+	/// Sets how to treat posts tagged as spoiler. Valid values are `allow` and `skip`
+	#[structopt(long, default_value = "allow")]
+	spoiler: Policy,
+
+	/// Sets how to treat crossposts. defaults to `resolve`
+	///
+	/// `resolve` downloads from the original post instead, so the same image is not downloaded
+	/// twice under different subreddits. `allow` treats crossposts as normal posts, while `skip`
+	/// ignores them.
+	#[structopt(long, default_value = "resolve")]
+	crosspost: CrosspostPolicy,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -0,0 +1,121 @@
+use anyhow::{bail, Result};
+use structopt::StructOpt;
+
+use crate::api::config::{
+	config::{write_config, Config},
+	transform::{Crop, OutputFormat},
+};
+
+#[derive(StructOpt, Debug, Clone, Copy)]
+pub enum Transform {
+	/// Enables image transform for focused profile
+	#[structopt(visible_aliases = &["enabled", "e"])]
+	Enable,
+	/// Disables image transform for focused profile
+	#[structopt(visible_aliases = &["disabled", "d"])]
+	Disable,
+	/// Sets how images are cropped to the aspect ratio of focused profile
+	///
+	/// Valid values are `center` (default), `smart` (keeps the most detailed part of the image)
+	/// and `off`.
+	Crop { input: Crop },
+	/// Sets the size images are downscaled to fit for focused profile. 0 means no limit
+	Size { width: u32, height: u32 },
+	/// Sets the format transformed images are encoded in for focused profile
+	///
+	/// Valid values are `original` (default), `jpg` and `png`.
+	Format { input: OutputFormat },
+	/// Sets jpeg quality (1-100) of transformed images for focused profile
+	Quality { input: u8 },
+	/// Keeps the untouched image under `.originals` directory of focused profile
+	KeepOriginal {
+		#[structopt(parse(try_from_str))]
+		input: bool,
+	},
+}
+
+impl Transform {
+	pub async fn handle(&self, config: &mut Config) -> Result<()> {
+		match self {
+			Transform::Enable => self.enable(true, config).await?,
+			Transform::Disable => self.enable(false, config).await?,
+			Transform::Crop { input } => self.crop(*input, config).await?,
+			Transform::Size { width, height } => self.size(*width, *height, config).await?,
+			Transform::Format { input } => self.format(*input, config).await?,
+			Transform::Quality { input } => self.quality(*input, config).await?,
+			Transform::KeepOriginal { input } => self.keep_original(*input, config).await?,
+		}
+		Ok(())
+	}
+
+	async fn enable(&self, input: bool, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.enable = input;
+		write_config(config).await?;
+		let status = if input { "enabled" } else { "disabled" };
+		println!(
+			"image transform {} for '{}'",
+			status, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn crop(&self, input: Crop, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.crop = input;
+		write_config(config).await?;
+		println!(
+			"transform crop is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn size(&self, width: u32, height: u32, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.width = width;
+		cfg.transform.height = height;
+		write_config(config).await?;
+		println!(
+			"transform size is set to '{}x{}' for '{}'",
+			width, height, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn format(&self, input: OutputFormat, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.format = input;
+		write_config(config).await?;
+		println!(
+			"transform format is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn quality(&self, input: u8, config: &mut Config) -> Result<()> {
+		if !(1..=100).contains(&input) {
+			bail!("quality must be between 1 and 100")
+		}
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.quality = input;
+		write_config(config).await?;
+		println!(
+			"transform quality is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+
+	async fn keep_original(&self, input: bool, config: &mut Config) -> Result<()> {
+		let cfg = config.get_mut_configuration()?;
+		cfg.transform.keep_original = input;
+		write_config(config).await?;
+		println!(
+			"keep original is set to '{}' for '{}'",
+			input, config.focused_profile
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use structopt::StructOpt;
+use tokio::{fs, task};
+
+use super::dedup::{list_files, list_symlinks};
+use crate::api::{
+	config::config::Config,
+	history::{database::History, integrity::check_image},
+};
+
+pub static QUARANTINE_DIR: &str = ".quarantine";
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Verify {
+	/// Only prints the broken files found without touching any file
+	#[structopt(long)]
+	dry_run: bool,
+	/// Keeps download history of quarantined files, so they will not be downloaded again
+	#[structopt(long)]
+	keep_history: bool,
+}
+
+impl Verify {
+	/// Scans every profile directory for truncated or undecodable images, leftover `.part` files
+	/// from interrupted copies, and symbolic links whose image is gone. Broken files are moved to
+	/// `.quarantine` directory under the download path.
+	pub async fn handle(&self, config: &Config) -> Result<()> {
+		let history = History::open_default()?;
+		let mut dirs = Vec::new();
+		for profile in config.settings.keys() {
+			let dir = config.path.join(profile);
+			if fs::metadata(&dir).await.is_ok() {
+				dirs.push(dir);
+			}
+		}
+
+		let mut total = 0;
+		for dir in &dirs {
+			let mut files = list_files(dir).await?;
+			files.sort();
+			for file in files {
+				let reason = match file.extension() {
+					Some(ext) if ext == "part" => "unfinished copy".to_string(),
+					// metadata sidecar
+					Some(ext) if ext == "json" => continue,
+					_ => {
+						let path = file.clone();
+						match task::spawn_blocking(move || check_image(path)).await? {
+							Ok(_) => continue,
+							Err(err) => err.to_string(),
+						}
+					}
+				};
+				total += 1;
+				self.handle_broken(&file, &reason, config, &history).await?;
+			}
+		}
+		// links are checked last, quarantined files above may be the target of links.
+		for dir in &dirs {
+			let mut links = list_symlinks(dir).await?;
+			links.sort();
+			for link in links {
+				if fs::metadata(&link).await.is_ok() {
+					continue;
+				}
+				total += 1;
+				self.handle_broken(&link, "dangling symbolic link", config, &history)
+					.await?;
+			}
+		}
+		println!("found {} broken file(s)", total);
+		Ok(())
+	}
+
+	async fn handle_broken(
+		&self,
+		file: &Path,
+		reason: &str,
+		config: &Config,
+		history: &History,
+	) -> Result<()> {
+		if self.dry_run {
+			println!("{}: {}", file.display(), reason);
+			return Ok(());
+		}
+		let relative = file.strip_prefix(&config.path).unwrap_or(file);
+		let target = config.path.join(QUARANTINE_DIR).join(relative);
+		self.quarantine(file, &target).await?;
+		if !self.keep_history {
+			history.forget_file(file)?;
+		}
+		history.remove_perceptual_hash(file)?;
+		println!(
+			"{}: {}. moved to {}",
+			file.display(),
+			reason,
+			target.display()
+		);
+		Ok(())
+	}
+
+	async fn quarantine(&self, file: &Path, target: &Path) -> Result<()> {
+		if let Some(parent) = target.parent() {
+			fs::create_dir_all(parent).await.with_context(|| {
+				format!(
+					"failed to create quarantine directory on: {}",
+					parent.display()
+				)
+			})?;
+		}
+		fs::rename(file, target)
+			.await
+			.with_context(|| format!("failed to move {} to {}", file.display(), target.display()))
+	}
+}
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
 use super::ridit_proto::{
-	AspectRatio, AspectRatioOptional, MinimumSize, MinimumSizeOptional, ProfileData,
-	ProfileListMap, ProfileUpsert,
+	AspectRatio, AspectRatioOptional, ExactResolution, ExactResolutionOptional, MaximumSize,
+	MaximumSizeOptional, MinimumSize, MinimumSizeOptional, ProfileData, ProfileListMap,
+	ProfileUpsert,
 };
 use crate::api::config::{
 	config::{read_config, write_config, Config, Settings},
-	configuration::Configuration,
+	configuration::{Configuration, Resolution},
 };
 use tonic::Status;
 
@@ -30,6 +31,15 @@ impl Config {
 			cfg.server_update_minimum_size(new_ms);
 		}
 
+		if let Some(new_ms) = profile_upsert.maximum_size {
+			cfg.server_update_maximum_size(new_ms);
+		}
+
+		if let Some(new_er) = profile_upsert.exact_resolution {
+			cfg.server_update_exact_resolution(new_er)
+				.map_err(Status::invalid_argument)?;
+		}
+
 		self.insert(profile_upsert.name, cfg);
 		self.server_write_config().await?;
 		Ok(())
@@ -61,7 +71,32 @@ impl Configuration {
 	pub fn server_update_minimum_size(&mut self, msp: MinimumSizeOptional) {
 		self.minimum_size.enable = msp.enable.unwrap_or(self.minimum_size.enable);
 		self.minimum_size.height = msp.height.unwrap_or(self.minimum_size.height);
-		self.minimum_size.width = msp.height.unwrap_or(self.minimum_size.width);
+		self.minimum_size.width = msp.width.unwrap_or(self.minimum_size.width);
+	}
+
+	/// Server side implementation. Handles Maximum Size gRPC data.
+	pub fn server_update_maximum_size(&mut self, msp: MaximumSizeOptional) {
+		self.maximum_size.enable = msp.enable.unwrap_or(self.maximum_size.enable);
+		self.maximum_size.height = msp.height.unwrap_or(self.maximum_size.height);
+		self.maximum_size.width = msp.width.unwrap_or(self.maximum_size.width);
+	}
+
+	/// Server side implementation. Handles Exact Resolution gRPC data. Returns error message on
+	/// invalid resolution.
+	pub fn server_update_exact_resolution(
+		&mut self,
+		erp: ExactResolutionOptional,
+	) -> Result<(), String> {
+		self.exact_resolution.enable = erp.enable.unwrap_or(self.exact_resolution.enable);
+		self.exact_resolution.tolerance = erp.tolerance.unwrap_or(self.exact_resolution.tolerance);
+		if let Some(list) = erp.resolutions {
+			self.exact_resolution.resolutions = list
+				.value
+				.iter()
+				.map(|res| res.parse::<Resolution>())
+				.collect::<Result<_, _>>()?;
+		}
+		Ok(())
 	}
 }
 
@@ -79,6 +114,21 @@ impl From<Configuration> for ProfileData {
 				height: cfg.minimum_size.height,
 				width: cfg.minimum_size.width,
 			}),
+			maximum_size: Some(MaximumSize {
+				enable: cfg.maximum_size.enable,
+				height: cfg.maximum_size.height,
+				width: cfg.maximum_size.width,
+			}),
+			exact_resolution: Some(ExactResolution {
+				enable: cfg.exact_resolution.enable,
+				tolerance: cfg.exact_resolution.tolerance,
+				resolutions: cfg
+					.exact_resolution
+					.resolutions
+					.iter()
+					.map(|res| res.to_string())
+					.collect(),
+			}),
 		}
 	}
 }
@@ -24,7 +24,9 @@ pub async fn start_server(config: Config) -> anyhow::Result<()> {
 	let addr = SocketAddr::new(config.server.ip, config.server.port);
 
 	let config = Arc::new(Mutex::new(config));
-	let ridit_server = RiditServer::new(RiditController::new(config.clone()));
+	let controller = RiditController::new(config.clone());
+	tokio::spawn(controller.clone().run_scheduler());
+	let ridit_server = RiditServer::new(controller);
 	let profile_server = ProfileServer::new(ProfileController);
 
 	Server::builder()
@@ -1,18 +1,20 @@
-use std::{
-	ops::Add,
-	sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use crate::api::{
 	config::config::{read_config, Config},
-	reddit::repository::{PrintOut, Repository},
+	reddit::{
+		models::download_status::DownloadStatus,
+		repository::{PrintOut, Repository},
+	},
 };
 
 use super::ridit_proto::ridit_server::Ridit;
 use super::ridit_proto::{AppState, DownloadStatus as ProtoDownloadStatus, EmptyMsg};
-use anyhow::Error;
-use chrono::{DateTime, Duration, Local, SecondsFormat, Timelike};
-use tokio::sync::mpsc;
+use chrono::{DateTime, Duration, Local, SecondsFormat};
+use tokio::{
+	sync::mpsc::{self, UnboundedSender},
+	time::{self, MissedTickBehavior},
+};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tonic::{Request, Response, Status};
 
@@ -26,18 +28,21 @@ pub struct RiditController {
 pub struct State {
 	pub status: u32,
 	pub message: String,
-	pub next_download_time: DateTime<Local>,
+	/// `None` when scheduled download is disabled.
+	pub next_download_time: Option<DateTime<Local>>,
+}
+
+impl State {
+	pub const STANDBY: u32 = 0;
+	pub const DOWNLOADING: u32 = 1;
 }
 
 impl Default for State {
 	fn default() -> Self {
 		State {
-			status: 0,
+			status: Self::STANDBY,
 			message: "standby".to_string(),
-			next_download_time: Local::now()
-				.add(Duration::minutes(1))
-				.with_second(0)
-				.unwrap(),
+			next_download_time: None,
 		}
 	}
 }
@@ -49,7 +54,8 @@ impl From<State> for AppState {
 			message: state.message.to_owned(),
 			next_download_time: state
 				.next_download_time
-				.to_rfc3339_opts(SecondsFormat::Secs, true),
+				.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+				.unwrap_or_default(),
 		}
 	}
 }
@@ -61,6 +67,82 @@ impl RiditController {
 			state: Arc::new(Mutex::new(State::default())),
 		}
 	}
+
+	/// Runs download on the interval set in server configuration. Returns immediately if the
+	/// interval is 0 (disabled).
+	///
+	/// A tick is skipped when previous download is still in progress.
+	pub async fn run_scheduler(self) {
+		let minutes = self.config.lock().unwrap().server.download_interval;
+		if minutes == 0 {
+			return;
+		}
+		let period = Duration::minutes(minutes.into());
+		let mut interval = time::interval(period.to_std().unwrap());
+		interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+		loop {
+			interval.tick().await;
+			self.state.lock().unwrap().next_download_time = Some(Local::now() + period);
+			if !self.begin_download() {
+				continue;
+			}
+			let config = match read_config().await {
+				Ok(config) => config,
+				Err(err) => {
+					self.finish_download(format!("failed to read configuration: {}", err));
+					continue;
+				}
+			};
+			let (tx, mut rx) = mpsc::unbounded_channel();
+			// nobody listens to scheduled downloads, but the channel must stay open.
+			tokio::spawn(async move { while rx.recv().await.is_some() {} });
+			self.download(config, tx).await;
+		}
+	}
+
+	/// Marks state as downloading. Returns false if there is already a download in progress.
+	fn begin_download(&self) -> bool {
+		let mut state = self.state.lock().unwrap();
+		if state.status == State::DOWNLOADING {
+			return false;
+		}
+		state.status = State::DOWNLOADING;
+		state.message = "downloading".to_string();
+		true
+	}
+
+	fn finish_download(&self, message: String) {
+		let mut state = self.state.lock().unwrap();
+		state.status = State::STANDBY;
+		state.message = message;
+	}
+
+	/// Runs the download and returns to standby state, whether the download succeeds or not.
+	async fn download(&self, config: Config, tx: UnboundedSender<DownloadStatus>) {
+		let repo = match Repository::new(Arc::new(config)) {
+			Ok(repo) => repo,
+			Err(err) => {
+				self.finish_download(format!("failed to start download: {:#}", err));
+				return;
+			}
+		};
+		// a panicking download must not leave the state stuck on downloading.
+		let result =
+			match tokio::spawn(async move { repo.download(PrintOut::None, tx).await }).await {
+				Ok(result) => result,
+				Err(err) => {
+					self.finish_download(format!("download stopped unexpectedly: {}", err));
+					return;
+				}
+			};
+		let failed = result.iter().filter(|(_, op)| op.is_err()).count();
+		self.finish_download(format!(
+			"last download finished at {}: {} images processed, {} failed",
+			Local::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+			result.len(),
+			failed
+		));
+	}
 }
 
 #[tonic::async_trait]
@@ -81,32 +163,19 @@ impl Ridit for RiditController {
 		let (tx, mut rx) = mpsc::unbounded_channel();
 		let (tx_proto, rx_proto) = mpsc::unbounded_channel::<Result<ProtoDownloadStatus, Status>>();
 
-		{
-			let mut state = self.state.lock().unwrap();
-			*state = State {
-				status: 1,
-				message: "downloading".to_string(),
-				next_download_time: Local::now()
-					.add(Duration::minutes(1))
-					.with_second(0)
-					.unwrap(),
-			};
+		if !self.begin_download() {
+			return Err(Status::unavailable("a download is already in progress"));
 		}
 
 		tokio::spawn({
-			let state = self.state.clone();
-			async move {
-				let repo = Repository::new(Arc::new(config));
-				// TODO: add sqlite integration later
-				repo.download(PrintOut::None, tx).await;
-				*state.lock().unwrap() = State::default();
-				Ok::<(), Error>(())
-			}
+			let this = self.clone();
+			async move { this.download(config, tx).await }
 		});
 
 		tokio::spawn(async move {
 			while let Some(status) = rx.recv().await {
-				tx_proto.send(Ok(status.into())).unwrap();
+				// client may have disconnected, keep draining so download is not interrupted.
+				tx_proto.send(Ok(status.into())).ok();
 			}
 		});
 